@@ -0,0 +1,391 @@
+use std::collections::HashMap;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use moonlight::moonlight::debugable::debug::ColorMode;
+use moonlight::moonlight::instruction_memory_loadable::ImageFormat;
+use moonlight::moonlight::lintable::*;
+use moonlight::moonlight::utils::{FillPattern, ISA};
+use moonlight::moonlight::Verbosity;
+
+pub const SUBCOMMANDS: [(&str, &str); 6] = [
+    ("assemble", "Assemble a program into an instruction image"),
+    ("run", "Check a program and load its data memory"),
+    ("check", "Check a program, optionally running the lints"),
+    ("disasm", "Disassemble an instruction image"),
+    ("fmt", "Format source files in place"),
+    ("lsp", "Start the language server over stdio"),
+];
+
+// Every subcommand lays out its help the same way, with the option list from clap
+const HELP_TEMPLATE: &str = "\
+{about-with-newline}
+Usage: {usage}
+
+{all-args}{after-help}";
+
+// Profile options and the machine profile setting each one overrides
+const PROFILE_OPTIONS: [(&str, &str); 5] = [
+    ("data-memory", "data_memory_size"),
+    ("instruction-memory", "instruction_memory_size"),
+    ("io-window", "io_window"),
+    ("stack-pointer", "initial_stack_pointer"),
+    ("reset-pc", "reset_pc"),
+];
+
+pub struct Options {
+    pub subcommand: String,
+    pub positionals: Vec<String>,
+
+    pub color_mode: ColorMode,
+    pub fill_pattern: FillPattern,
+    pub verbosity: Verbosity,
+
+    pub include_dirs: Vec<String>,
+    pub defines: HashMap<String, String>,
+    pub entry_label: Option<String>,
+    pub map_path: Option<String>,
+    pub optimize: bool,
+    pub layout_path: Option<String>,
+    pub profile_path: Option<String>,
+    pub profile_settings: Vec<(String, String)>, // Applied over the profile file, in order
+
+    pub output_path: Option<String>,
+    pub image_format: ImageFormat,
+
+    pub lint: bool,
+    pub lint_levels: HashMap<Lint, LintLevel>,
+
+    pub check: bool,
+}
+
+fn summary_of(subcommand: &str) -> &'static str {
+    SUBCOMMANDS
+        .iter()
+        .find(|(name, _)| *name == subcommand)
+        .map_or("", |(_, summary)| summary)
+}
+
+fn global_args() -> Vec<Arg> {
+    vec![
+        Arg::new("color")
+            .long("color")
+            .value_name("auto|always|never")
+            .help("When to color the output [default: auto]")
+            .value_parser(|name: &str| {
+                ColorMode::from_name(name).ok_or(format!("Unknown color mode {}, expected auto, always or never", name))
+            })
+            .global(true)
+            .display_order(100),
+        Arg::new("fill")
+            .long("fill")
+            .value_name("pattern")
+            .help("Fill data memory with zero, random:<seed> or const:<value> [default: zero]")
+            .value_parser(|name: &str| FillPattern::from_name(name))
+            .global(true)
+            .display_order(100),
+        Arg::new("quiet")
+            .short('q')
+            .long("quiet")
+            .help("Do not print warnings")
+            .action(ArgAction::SetTrue)
+            .global(true)
+            .display_order(100),
+        Arg::new("verbose")
+            .long("verbose")
+            .help("Print what each stage did")
+            .action(ArgAction::SetTrue)
+            .global(true)
+            .display_order(100),
+    ]
+}
+
+fn source_args() -> Vec<Arg> {
+    vec![
+        Arg::new("file").value_name("file").help("The main source file").required(true),
+        Arg::new("include")
+            .short('I')
+            .long("include")
+            .value_name("dir")
+            .help("Also look for included files in <dir>")
+            .action(ArgAction::Append),
+        Arg::new("define")
+            .short('D')
+            .long("define")
+            .value_name("NAME=VALUE")
+            .help("Replace NAME by VALUE while scanning")
+            .value_parser(|define: &str| match define.split_once('=') {
+                Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+                _ => Err(format!("Expected NAME=VALUE, found {}", define)),
+            })
+            .action(ArgAction::Append),
+        Arg::new("entry")
+            .long("entry")
+            .value_name("label")
+            .help("Require <label> to be an instruction label"),
+        Arg::new("optimize")
+            .short('O')
+            .long("optimize")
+            .help("Remove redundant instructions with the peephole pass")
+            .action(ArgAction::SetTrue),
+        Arg::new("layout")
+            .long("layout")
+            .value_name("file")
+            .help("Place the data sections as described in <file>"),
+        Arg::new("map")
+            .long("map")
+            .value_name("path")
+            .help("Write the symbol addresses, sizes and memory usage to <path>"),
+        Arg::new("profile")
+            .long("profile")
+            .value_name("file")
+            .help("Read the machine profile from <file>"),
+        Arg::new("data-memory")
+            .long("data-memory")
+            .value_name("bytes")
+            .help("Data memory size, like 4K or 0x2000 [default: 32K]"),
+        Arg::new("instruction-memory")
+            .long("instruction-memory")
+            .value_name("words")
+            .help("Instruction memory size [default: 32K]"),
+        Arg::new("io-window")
+            .long("io-window")
+            .value_name("start:size")
+            .help("Reserve data memory bytes for I/O"),
+        Arg::new("stack-pointer")
+            .long("stack-pointer")
+            .value_name("address")
+            .help("Initial stack pointer [default: the data memory size]"),
+        Arg::new("reset-pc")
+            .long("reset-pc")
+            .value_name("address")
+            .help("Address of the first instruction run [default: 0]"),
+    ]
+}
+
+fn subcommand(name: &'static str) -> Command {
+    Command::new(name)
+        .about(summary_of(name))
+        .help_template(HELP_TEMPLATE)
+}
+
+fn instruction_list() -> String {
+    let mut list = String::from("Instructions:\n");
+    for spec in ISA.iter() {
+        list.push_str(&format!(
+            "  {:<6} {:<16} opcode 0b{:06b}  {}\n",
+            spec.mnemonic, spec.format.syntax(), spec.opcode, spec.summary
+        ));
+    }
+    list.push_str("\nA bare <file> is the same as moonlight run <file>.");
+    list
+}
+
+fn lint_list() -> String {
+    let names: Vec<&str> = LINTS.iter().map(|lint| lint.name()).collect();
+    format!("Lints:\n  {}", names.join("\n  "))
+}
+
+pub fn command() -> Command {
+    Command::new("moonlight")
+        .about("Assembler and tools for Moonlight programs")
+        .version("0.1.0")
+        .disable_version_flag(true)
+        .help_template(HELP_TEMPLATE)
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .args(global_args())
+        .arg(
+            Arg::new("version")
+                .short('v')
+                .long("version")
+                .help("Print the version")
+                .action(ArgAction::Version),
+        )
+        .after_help(instruction_list())
+        .subcommand(
+            subcommand("assemble")
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("path")
+                        .help("Where to write the image [default: <file> with the format's extension]"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_name("format")
+                        .help("bin: big-endian words [default]\nhex: one hex word per line\nsv-rom, vhdl-rom: ROM and data RAM modules")
+                        .value_parser(|name: &str| {
+                            ImageFormat::from_name(name)
+                                .ok_or(format!("Unknown image format {}, expected bin, hex, sv-rom or vhdl-rom", name))
+                        }),
+                )
+                .args(source_args()),
+        )
+        .subcommand(subcommand("run").args(source_args()))
+        .subcommand(
+            subcommand("check")
+                .arg(Arg::new("lint").long("lint").help("Also run the lints").action(ArgAction::SetTrue))
+                .arg(lint_level_arg("allow", "Do not report <lint>"))
+                .arg(lint_level_arg("deny", "Report <lint> as an error"))
+                .args(source_args())
+                .after_help(lint_list()),
+        )
+        .subcommand(
+            subcommand("disasm")
+                .after_help(
+                    "Only the opcodes come from the machine. The operand layout after them is provisional\n\
+                     until the hardware spec defines it, so operands decoded from real ROM dumps may be wrong.\n\
+                     Branch comments name the label reached when offsets count from the branch itself.",
+                )
+                .arg(Arg::new("image").value_name("image").help("The instruction image, in big-endian words").required(true))
+                .arg(Arg::new("symbols").value_name("symbols").help("A map written by --map, whose instruction labels name addresses")),
+        )
+        .subcommand(
+            subcommand("fmt")
+                .arg(Arg::new("files").value_name("files").help("The source files to format").required(true).num_args(1..))
+                .arg(
+                    Arg::new("check")
+                        .long("check")
+                        .help("List unformatted files instead of rewriting them")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(subcommand("lsp"))
+}
+
+fn lint_level_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name)
+        .long(name)
+        .value_name("lint")
+        .help(help)
+        .value_parser(|name: &str| Lint::from_name(name).ok_or(format!("Unknown lint {}", name)))
+        .action(ArgAction::Append)
+}
+
+// Values of a repeatable option together with where they appeared on the command line
+fn indexed_values<T: Clone + Send + Sync + 'static>(matches: &ArgMatches, id: &str) -> Vec<(usize, T)> {
+    match (matches.indices_of(id), matches.get_many::<T>(id)) {
+        (Some(indices), Some(values)) => indices.zip(values.cloned()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/*
+    A bare file path is kept working as an alias for run, so run is
+    inserted before the first argument that is neither a global option
+    nor a subcommand.
+ */
+fn with_implicit_run(args: &[String]) -> Vec<String> {
+    let mut index = 0;
+    while let Some(arg) = args.get(index) {
+        match arg.as_str() {
+            "-q" | "--quiet" | "--verbose" => index += 1,
+            "--color" | "--fill" => index += 2,
+            _ => break,
+        }
+    }
+
+    let mut args = args.to_vec();
+    if let Some(arg) = args.get(index)
+        && !arg.starts_with('-')
+        && arg != "help"
+        && !SUBCOMMANDS.iter().any(|(subcommand, _)| subcommand == arg)
+    {
+        args.insert(index, "run".to_string());
+    }
+    args
+}
+
+/*
+    args does not include the program name. Help, the version and usage
+    errors come back as a clap::Error, which prints itself on exit.
+ */
+pub fn parse_args(args: &[String]) -> Result<Options, clap::Error> {
+    let matches = command().try_get_matches_from(
+        std::iter::once("moonlight".to_string()).chain(with_implicit_run(args))
+    )?;
+    let (subcommand, sub_matches) = match matches.subcommand() {
+        Some(subcommand) => subcommand,
+        None => unreachable!(),
+    };
+
+    let verbosity = if sub_matches.get_flag("quiet") {
+        Verbosity::Quiet
+    } else if sub_matches.get_flag("verbose") {
+        Verbosity::Verbose
+    } else {
+        Verbosity::Normal
+    };
+    let reads_source = matches!(subcommand, "assemble" | "run" | "check");
+    // Options a subcommand does not define read as unset
+    let string = |id: &str| sub_matches.try_get_one::<String>(id).ok().flatten().cloned();
+
+    let positionals: Vec<String> = ["file", "image", "symbols", "files"]
+        .iter()
+        .filter_map(|id| sub_matches.try_get_many::<String>(id).ok().flatten())
+        .flatten()
+        .cloned()
+        .collect();
+
+    let mut options = Options {
+        subcommand: subcommand.to_string(),
+        positionals,
+        color_mode: sub_matches.get_one::<ColorMode>("color").copied().unwrap_or(ColorMode::Auto),
+        fill_pattern: sub_matches.get_one::<FillPattern>("fill").cloned().unwrap_or_default(),
+        verbosity,
+        include_dirs: Vec::new(),
+        defines: HashMap::new(),
+        entry_label: None,
+        map_path: None,
+        optimize: false,
+        layout_path: None,
+        profile_path: None,
+        profile_settings: Vec::new(),
+        output_path: string("output"),
+        image_format: sub_matches
+            .try_get_one::<ImageFormat>("format")
+            .ok()
+            .flatten()
+            .copied()
+            .unwrap_or(ImageFormat::Bin),
+        lint: sub_matches.try_get_one::<bool>("lint").ok().flatten().copied().unwrap_or(false),
+        lint_levels: HashMap::new(),
+        check: sub_matches.try_get_one::<bool>("check").ok().flatten().copied().unwrap_or(false),
+    };
+
+    if reads_source {
+        options.include_dirs = sub_matches.get_many::<String>("include").into_iter().flatten().cloned().collect();
+        options.defines = sub_matches.get_many::<(String, String)>("define").into_iter().flatten().cloned().collect();
+        options.entry_label = string("entry");
+        options.map_path = string("map");
+        options.optimize = sub_matches.get_flag("optimize");
+        options.layout_path = string("layout");
+        options.profile_path = string("profile");
+
+        // Later settings win, like data_memory_size moving a default stack pointer
+        let mut profile_settings: Vec<(usize, String, String)> = Vec::new();
+        for (id, key) in PROFILE_OPTIONS.iter() {
+            for (index, value) in indexed_values::<String>(sub_matches, id) {
+                profile_settings.push((index, key.to_string(), value));
+            }
+        }
+        profile_settings.sort_by_key(|(index, _, _)| *index);
+        options.profile_settings = profile_settings.into_iter().map(|(_, key, value)| (key, value)).collect();
+    }
+
+    if subcommand == "check" {
+        let mut lint_levels: Vec<(usize, Lint, LintLevel)> = Vec::new();
+        for (id, level) in [("allow", LintLevel::Allow), ("deny", LintLevel::Deny)] {
+            for (index, lint) in indexed_values::<Lint>(sub_matches, id) {
+                lint_levels.push((index, lint, level));
+            }
+        }
+        lint_levels.sort_by_key(|(index, _, _)| *index);
+        options.lint_levels = lint_levels.into_iter().map(|(_, lint, level)| (lint, level)).collect();
+    }
+
+    Ok(options)
+}
@@ -1,25 +1,70 @@
+mod cli;
+
 use moonlight::moonlight;
+use moonlight::debugable::debug;
+use moonlight::disassemblable::Disassemblable;
+use moonlight::formattable::Formattable;
+use moonlight::lintable::*;
+use moonlight::language_server::LanguageServer;
+use moonlight::utils::MachineProfile;
 
 pub fn main() {
-    let args = std::env::args().collect::<Vec<String>>();
-    match args.len() {
-        1 => {
-            println!("Please provide a file path as an argument.");
-            return;
-        }
-        2 => {
-            match args[1].as_str() {
-                "--version" | "-v" => {
-                    println!("Moonlight version 0.1.0");
-                }
-                _ => {
-                    let mut ml = moonlight::Moonlight::new();
-                    ml.run(&args[1]);
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let options = match cli::parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => e.exit(),
+    };
+
+    debug::set_color_mode(options.color_mode);
+
+    let mut machine_profile = MachineProfile::default();
+    let profile_result = options.profile_path
+        .as_ref()
+        .map_or(Ok(()), |profile_path| machine_profile.load_file(profile_path))
+        .and_then(|_| {
+            options.profile_settings
+                .iter()
+                .try_for_each(|(key, value)| machine_profile.set(key, value))
+        })
+        .and_then(|_| machine_profile.validate());
+    if let Err(e) = profile_result {
+        println!("{} {} {}", debug::interpreter(), debug::error(), e);
+        std::process::exit(2);
+    }
+
+    let mut ml = moonlight::Moonlight::with_machine_profile(machine_profile, options.fill_pattern.clone());
+    ml.verbosity = options.verbosity;
+    ml.include_dirs = options.include_dirs.clone();
+    ml.defines = options.defines.clone();
+    ml.entry_label = options.entry_label.clone();
+    ml.layout_path = options.layout_path.clone();
+    ml.map_path = options.map_path.clone();
+    ml.optimize_instructions = options.optimize;
+
+    match options.subcommand.as_str() {
+        "assemble" => {
+            let file_path = &options.positionals[0];
+            let output_path = match options.output_path {
+                Some(ref output_path) => output_path.clone(),
+                None => {
+                    std::path::Path::new(file_path)
+                        .with_extension(options.image_format.extension())
+                        .to_string_lossy()
+                        .to_string()
                 }
-            }
+            };
+            ml.assemble(file_path, &output_path, options.image_format);
         }
-        _ => {
-            println!("Too many arguments provided. Please provide only one file path.");
+        "run" => ml.run(&options.positionals[0]),
+        "check" => {
+            let ast = ml.check(&options.positionals[0]);
+            if options.lint {
+                ml.report_lints(&ast, &options.lint_levels);
+            }
         }
+        "disasm" => ml.disassemble_file(&options.positionals[0], options.positionals.get(1).map(|s| s.as_str())),
+        "fmt" => ml.format_files(&options.positionals, options.check),
+        "lsp" => LanguageServer::new().run(),
+        _ => unreachable!(),
     }
-}
\ No newline at end of file
+}
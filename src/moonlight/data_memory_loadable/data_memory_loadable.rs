@@ -6,13 +6,16 @@ use crate::moonlight::utils::*;
 use crate::moonlight::Moonlight;
 
 pub trait DataMemoryLoadable {
-    fn load_data_memory_from(&mut self, ast: &Ast);
+    fn load_data_memory_from(&mut self, ast: &Ast) -> Result<(), Diagnostic>;
 }
 
 impl DataMemoryLoadable for Moonlight {
-    fn load_data_memory_from(&mut self, ast: &Ast) {
+    fn load_data_memory_from(&mut self, ast: &Ast) -> Result<(), Diagnostic> {
         let mut stack_counter: usize = 0;
         for data_camp in ast.data_field.iter() {
+            if let Some(address) = data_camp.address {
+                stack_counter = address;
+            }
 
             match data_camp.directive.token {
                 
@@ -29,11 +32,11 @@ impl DataMemoryLoadable for Moonlight {
                                             Some(memory_cell) => {
                                                 *memory_cell = n;
                                             }
-                                            None => self.exit_with_positional_error("Stack overflow while loading data memory.", vptk.position),
+                                            None => return Err(Diagnostic::positional_error("Stack overflow while loading data memory.", vptk.position)),
                                         }
                                         stack_counter += 1;
                                     }
-                                    Err(e) => self.exit_with_positional_error(e.as_str(), vptk.position),
+                                    Err(e) => return Err(Diagnostic::positional_error(e.as_str(), vptk.position)),
                                 }
                             } else {unreachable!();}
                         } 
@@ -56,19 +59,19 @@ impl DataMemoryLoadable for Moonlight {
                                                 Some(mem_cell_1) => {
                                                     *mem_cell_1 = b;
                                                 }
-                                                None => self.exit_with_positional_error("Stack overflow while loading data memory.", vptk.position),
+                                                None => return Err(Diagnostic::positional_error("Stack overflow while loading data memory.", vptk.position)),
                                             }
 
                                             match self.data_memory.get_mut(stack_counter + 1) {
                                                 Some(mem_cell_2) => {
                                                     *mem_cell_2 = a;
                                                 }
-                                                None => self.exit_with_positional_error("Stack overflow while loading data memory.", vptk.position),
+                                                None => return Err(Diagnostic::positional_error("Stack overflow while loading data memory.", vptk.position)),
                                             }
                                         }
                                         stack_counter += 2;
                                     }
-                                    Err(e) => self.exit_with_positional_error(e.as_str(), vptk.position),
+                                    Err(e) => return Err(Diagnostic::positional_error(e.as_str(), vptk.position)),
                                 }
                             } else {unreachable!();}
                         } 
@@ -84,15 +87,23 @@ impl DataMemoryLoadable for Moonlight {
                         if let Token::Number(ref number_token) = ptk.token {
                             match number_token.to_u16() {
                                 Ok(num) => {
+                                    // Unlike plain .space, .bss is cleared whatever the fill pattern
+                                    if data_camp.section == BSS_SECTION {
+                                        for address in stack_counter..stack_counter + num as usize {
+                                            if let Some(memory_cell) = self.data_memory.get_mut(address) {
+                                                *memory_cell = 0;
+                                            }
+                                        }
+                                    }
                                     match stack_counter.checked_add(num as usize) {
                                         Some(v) => stack_counter = v,
-                                        None => self.exit_with_positional_error("Stack overflow while loading data memory.", ptk.position),
+                                        None => return Err(Diagnostic::positional_error("Stack overflow while loading data memory.", ptk.position)),
                                     }
                                     if stack_counter >= self.data_memory.len() {
-                                        self.exit_with_positional_error("Stack overflow while loading data memory.", ptk.position);
+                                        return Err(Diagnostic::positional_error("Stack overflow while loading data memory.", ptk.position));
                                     }
                                 }
-                                Err(e) => self.exit_with_positional_error(e.as_str(), ptk.position),
+                                Err(e) => return Err(Diagnostic::positional_error(e.as_str(), ptk.position)),
                             }
                         } else {unreachable!();}
                     } else {unreachable!();}
@@ -102,5 +113,48 @@ impl DataMemoryLoadable for Moonlight {
             }
             
         }
+
+        /*
+            Everything below the highest section end belongs to labelled
+            data, so the stack must never grow down past this point.
+         */
+        self.static_data_end = self.sections
+            .iter()
+            .map(|placement| placement.end)
+            .fold(stack_counter, usize::max);
+
+        /*
+            Sections may sit anywhere, even past the I/O window, so each
+            one is tested on its own. Without a layout the data field is
+            the single range below static_data_end.
+         */
+        if let Some((io_start, io_size)) = self.machine_profile.io_window
+            && io_size > 0
+        {
+            let io_end = io_start + io_size;
+            let ranges: Vec<(String, usize, usize)> = if self.sections.is_empty() {
+                vec![("data".to_string(), 0, self.static_data_end)]
+            } else {
+                self.sections
+                    .iter()
+                    .map(|placement| (placement.name.clone(), placement.start, placement.end))
+                    .collect()
+            };
+            for (name, start, end) in ranges {
+                if start < end && start < io_end && io_start < end {
+                    return Err(Diagnostic::error(&format!(
+                        "Section {} spans 0x{:04x} to 0x{:04x} and overlaps the I/O window at 0x{:04x} to 0x{:04x}",
+                        name, start, end, io_start, io_end
+                    )));
+                }
+            }
+        }
+        if self.machine_profile.initial_stack_pointer < self.static_data_end {
+            return Err(Diagnostic::error(&format!(
+                "The data field ends at 0x{:04x}, past the initial stack pointer 0x{:04x}",
+                self.static_data_end, self.machine_profile.initial_stack_pointer
+            )));
+        }
+        Ok(())
     }
 }
\ No newline at end of file
@@ -1,19 +1,52 @@
 use colored::Colorize;
 use supports_color::Stream;
 
+use std::sync::atomic::{AtomicU8, Ordering};
+
 const INTERPRETER_NAME: &str = "MOONLIGHT";
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(ColorMode::Auto),
+            "always" => Some(ColorMode::Always),
+            "never" => Some(ColorMode::Never),
+            _ => None,
+        }
+    }
+}
+
+static COLOR_MODE: AtomicU8 = AtomicU8::new(ColorMode::Auto as u8);
+
+pub fn set_color_mode(color_mode: ColorMode) {
+    COLOR_MODE.store(color_mode as u8, Ordering::Relaxed);
+}
+
+fn use_color() -> bool {
+    match COLOR_MODE.load(Ordering::Relaxed) {
+        mode if mode == ColorMode::Always as u8 => true,
+        mode if mode == ColorMode::Never as u8 => false,
+        _ => match supports_color::on(Stream::Stdout) {
+            Some(color_level) => color_level.has_16m || color_level.has_256,
+            None => false,
+        },
+    }
+}
+
 pub fn interpreter() -> String {
     let text = format!("[{}]", INTERPRETER_NAME);
-    if let Some(color_level) = supports_color::on(Stream::Stdout) {
-        if color_level.has_16m || color_level.has_256 {
-            text
-                .bold()
-                .magenta()
-                .to_string()
-        } else {
-            text
-        }
+    if use_color() {
+        text
+            .bold()
+            .magenta()
+            .to_string()
     } else {
         text
     }
@@ -21,15 +54,47 @@ pub fn interpreter() -> String {
 
 pub fn error() -> String {
     let text = "[error]".to_string();
-    if let Some(color_level) = supports_color::on(Stream::Stdout) {
-        if color_level.has_16m || color_level.has_256 {
-            text
-                .bold()
-                .bright_red()
-                .to_string()
-        } else {
-            text
-        }
+    if use_color() {
+        text
+            .bold()
+            .bright_red()
+            .to_string()
+    } else {
+        text
+    }
+}
+
+pub fn warning() -> String {
+    let text = "[warning]".to_string();
+    if use_color() {
+        text
+            .bold()
+            .bright_yellow()
+            .to_string()
+    } else {
+        text
+    }
+}
+
+pub fn note() -> String {
+    let text = "[note]".to_string();
+    if use_color() {
+        text
+            .bold()
+            .bright_cyan()
+            .to_string()
+    } else {
+        text
+    }
+}
+
+pub fn info() -> String {
+    let text = "[info]".to_string();
+    if use_color() {
+        text
+            .bold()
+            .bright_blue()
+            .to_string()
     } else {
         text
     }
@@ -38,16 +103,12 @@ pub fn error() -> String {
 pub fn position(file: String, line: u32, column: Option<u32>) -> String {
     
     let text = format!("[file: {}, line: {}, column: {}]", file, line, column.unwrap_or(0));
-    if let Some(color_level) = supports_color::on(Stream::Stdout) {
-        if color_level.has_16m || color_level.has_256 {
-            text
-                .bold()
-                .yellow()
-                .to_string()
-        } else {
-            text
-        }
+    if use_color() {
+        text
+            .bold()
+            .yellow()
+            .to_string()
     } else {
         text
     }
-}
\ No newline at end of file
+}
@@ -1,16 +1,74 @@
-use crate::moonlight::Moonlight;
+use crate::moonlight::{Moonlight, Verbosity};
 use crate::moonlight::utils::*;
 
 use super::debug;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Note,
+    Error,
+}
+
+/*
+    What the stages of the pipeline report. Errors come back as the Err
+    of each stage, notes and infos are collected for the caller, and
+    only the command line prints them or exits on them.
+ */
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub position: Option<Position>,
+}
+
+impl Diagnostic {
+    pub fn error(message: &str) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            message: message.to_string(),
+            position: None,
+        }
+    }
+
+    pub fn positional_error(message: &str, position: Position) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            message: message.to_string(),
+            position: Some(position),
+        }
+    }
+
+    pub fn note(message: &str) -> Self {
+        Diagnostic {
+            severity: Severity::Note,
+            message: message.to_string(),
+            position: None,
+        }
+    }
+
+    pub fn info(message: &str) -> Self {
+        Diagnostic {
+            severity: Severity::Info,
+            message: message.to_string(),
+            position: None,
+        }
+    }
+}
 
 pub trait Debugable {
-    fn exit_with_positional_error(&self, error: &str, position: Position);
-    fn exit_with_error(&self, error: &str);
+    fn exit_with_positional_error(&self, error: &str, position: Position) -> !;
+    fn exit_with_error(&self, error: &str) -> !;
+    fn exit_with_diagnostic(&self, diagnostic: &Diagnostic) -> !;
+    fn print_positional_warning(&self, warning: &str, position: Position);
+    fn print_positional_error(&self, error: &str, position: Position);
+    fn print_note(&self, note: &str);
+    fn print_info(&self, info: &str);
+    fn print_diagnostics(&self, diagnostics: &[Diagnostic]);
 }
 
 impl Debugable for Moonlight {
-    fn exit_with_positional_error(&self, error: &str, position: Position) {
+    fn exit_with_positional_error(&self, error: &str, position: Position) -> ! {
         println!(
             "\n{} {} {} {}",
             debug::interpreter(),
@@ -22,16 +80,89 @@ impl Debugable for Moonlight {
                 position.column
             ),
         );
-        std::process::exit(0);
+        std::process::exit(1);
     }
 
-    fn exit_with_error(&self, error: &str) {
+    fn exit_with_error(&self, error: &str) -> ! {
         println!(
             "\n{} {} {}",
             debug::interpreter(),
             debug::error(),
             error
         );
-        std::process::exit(0);
+        std::process::exit(1);
     }
-}
\ No newline at end of file
+
+    fn exit_with_diagnostic(&self, diagnostic: &Diagnostic) -> ! {
+        match diagnostic.position {
+            Some(position) => self.exit_with_positional_error(&diagnostic.message, position),
+            None => self.exit_with_error(&diagnostic.message),
+        }
+    }
+
+    fn print_positional_warning(&self, warning: &str, position: Position) {
+        if self.verbosity == Verbosity::Quiet {
+            return;
+        }
+        println!(
+            "{} {} {} {}",
+            debug::interpreter(),
+            debug::warning(),
+            warning,
+            debug::position(
+                self.get_file_name(position.file),
+                position.line,
+                position.column
+            ),
+        );
+    }
+
+    fn print_positional_error(&self, error: &str, position: Position) {
+        println!(
+            "{} {} {} {}",
+            debug::interpreter(),
+            debug::error(),
+            error,
+            debug::position(
+                self.get_file_name(position.file),
+                position.line,
+                position.column
+            ),
+        );
+    }
+
+    fn print_note(&self, note: &str) {
+        if self.verbosity == Verbosity::Quiet {
+            return;
+        }
+        println!(
+            "{} {} {}",
+            debug::interpreter(),
+            debug::note(),
+            note
+        );
+    }
+
+    fn print_info(&self, info: &str) {
+        if self.verbosity < Verbosity::Verbose {
+            return;
+        }
+        println!(
+            "{} {} {}",
+            debug::interpreter(),
+            debug::info(),
+            info
+        );
+    }
+
+    // Errors are left out, they end the program through exit_with_diagnostic
+    fn print_diagnostics(&self, diagnostics: &[Diagnostic]) {
+        for diagnostic in diagnostics.iter() {
+            match diagnostic.severity {
+                Severity::Info => self.print_info(&diagnostic.message),
+                Severity::Note => self.print_note(&diagnostic.message),
+                Severity::Error => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use crate::moonlight::debugable::*;
+use crate::moonlight::map_generatable::*;
+use crate::moonlight::utils::*;
+use crate::moonlight::Moonlight;
+
+/*
+    Words are decoded with the field layout of the ISA table (see isa.rs),
+    which is provisional past the opcode. Words with unknown opcodes or
+    non-zero unused bits are not decoded.
+ */
+
+pub fn read_image(path: &str) -> Result<Vec<u16>, String> {
+    use std::fs;
+
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(_) => return Err(format!("The file {} does not exist or could not be read", path)),
+    };
+
+    if bytes.len() % 2 != 0 {
+        return Err(format!("The image {} has an odd number of bytes, expected 16-bit words", path));
+    }
+
+    Ok(
+        bytes
+            .chunks(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect()
+    )
+}
+
+pub fn decode_word(word: u16) -> Option<String> {
+    let spec = InstructionSpec::from_word(word)?;
+    let values = spec.decode(word)?;
+
+    let mut operands: Vec<String> = Vec::new();
+    for (field, value) in spec.format.fields().iter().zip(values.iter()) {
+        match field.kind {
+            OperandKind::Accumulator => operands.push(Accumulator::from_index(*value as u8)?.name().to_string()),
+            OperandKind::Register => operands.push(Register::from_index(*value as u8)?.name().to_string()),
+            OperandKind::Immediate => operands.push(value.to_string()),
+        }
+    }
+
+    if operands.is_empty() {
+        Some(spec.mnemonic.to_string())
+    } else {
+        Some(format!("{} {}", spec.mnemonic, operands.join(", ")))
+    }
+}
+
+// Instruction index a decoded jr, jrl or b*z word branches to, see relative_target in isa.rs
+fn branch_target(word: u16, index: usize) -> Option<usize> {
+    let spec = InstructionSpec::from_word(word)?;
+    if !spec.has_relative_target() {
+        return None;
+    }
+    let offset = *spec.decode(word)?.last()?;
+    relative_target(index, offset)
+}
+
+pub trait Disassemblable {
+    fn disassemble(&self, words: &[u16], instruction_symbols: &[(String, usize)]) -> String;
+    fn disassemble_file(&mut self, image_path: &str, map_path: Option<&str>);
+}
+
+impl Disassemblable for Moonlight {
+    /*
+        instruction_symbols are labels at instruction addresses, as read
+        from a map by read_instruction_symbols. Labels go before the
+        instruction they name, and the comment of a branch names the
+        label it reaches.
+     */
+    fn disassemble(&self, words: &[u16], instruction_symbols: &[(String, usize)]) -> String {
+        let mut labels_by_address: HashMap<usize, Vec<&String>> = HashMap::new();
+        for (label, address) in instruction_symbols.iter() {
+            labels_by_address.entry(*address).or_default().push(label);
+        }
+        for labels in labels_by_address.values_mut() {
+            labels.sort();
+        }
+
+        let mut output = String::from(".inst\n");
+        for (address, word) in words.iter().enumerate() {
+            if let Some(labels) = labels_by_address.get(&address) {
+                for label in labels {
+                    output.push_str(&format!("{}:\n", label));
+                }
+            }
+
+            let target_labels = branch_target(*word, address)
+                .and_then(|target| labels_by_address.get(&target))
+                .map(|labels| format!(", to {}", labels[0]))
+                .unwrap_or_default();
+            match decode_word(*word) {
+                Some(text) => output.push_str(&format!(
+                    "\t{:<24}# 0x{:04x}: 0x{:04x}{}\n", text, address, word, target_labels
+                )),
+                None => output.push_str(&format!(
+                    "\t{:<24}# 0x{:04x}: undecodable\n", format!(".raw 0x{:04x}", word), address
+                )),
+            }
+        }
+
+        output
+    }
+
+    fn disassemble_file(&mut self, image_path: &str, map_path: Option<&str>) {
+        let instruction_symbols = match map_path {
+            Some(map_path) => {
+                let symbols = std::fs::read_to_string(map_path)
+                    .map_err(|_| format!("The file {} does not exist or could not be read", map_path))
+                    .and_then(|map| read_instruction_symbols(&map).map_err(|e| format!("{} [file: {}]", e, map_path)));
+                match symbols {
+                    Ok(symbols) => symbols,
+                    Err(e) => self.exit_with_error(&e),
+                }
+            }
+            None => Vec::new(),
+        };
+
+        match read_image(image_path) {
+            Ok(words) => print!("{}", self.disassemble(&words, &instruction_symbols)),
+            Err(e) => self.exit_with_error(e.as_str()),
+        }
+    }
+}
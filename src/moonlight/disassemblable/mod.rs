@@ -0,0 +1,3 @@
+pub mod disassemblable;
+
+pub use disassemblable::*;
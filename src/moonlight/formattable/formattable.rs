@@ -0,0 +1,220 @@
+use crate::moonlight::debugable::*;
+use crate::moonlight::scanneable::*;
+use crate::moonlight::Moonlight;
+
+enum FormatItem {
+    Blank,
+    Comment {
+        text: String,
+        indented: bool,
+    },
+    Section {
+        directive: String,
+        comment: Option<String>,
+    },
+    Label {
+        name: String,
+        comment: Option<String>,
+    },
+    Statement {
+        mnemonic: String,
+        operands: String,
+        comment: Option<String>,
+    },
+}
+
+fn join_operands(tokens: &[&LosslessToken]) -> String {
+    let mut result = String::new();
+    let mut previous: Option<&str> = None;
+    for tk in tokens {
+        match tk.text.as_str() {
+            "," => result.push_str(", "),
+            "[" | "]" => result.push_str(&tk.text),
+            _ => {
+                if matches!(previous, Some(previous) if previous != "," && previous != "[") {
+                    result.push(' ');
+                }
+                result.push_str(&tk.text);
+            }
+        }
+        previous = Some(tk.text.as_str());
+    }
+    result
+}
+
+fn split_into_items(source: &str) -> Vec<FormatItem> {
+    let tokens = scan_string_and_generate_lossless_tokens(source, 0);
+    let mut items: Vec<FormatItem> = Vec::new();
+
+    for line in tokens.split(|tk| tk.kind == LosslessTokenKind::Newline) {
+        let indented = matches!(line.first(), Some(tk) if tk.kind == LosslessTokenKind::Whitespace);
+        let comment = line
+            .iter()
+            .find(|tk| tk.kind == LosslessTokenKind::Comment)
+            .map(|tk| tk.text.trim_end().to_string());
+        let significant: Vec<&LosslessToken> = line.iter().filter(|tk| !tk.is_trivia()).collect();
+
+        if significant.is_empty() {
+            match comment {
+                Some(text) => items.push(FormatItem::Comment { text, indented }),
+                None => items.push(FormatItem::Blank),
+            }
+            continue;
+        }
+
+        // Leading label declarations go on their own lines
+        let label_count = significant
+            .iter()
+            .take_while(|tk| tk.kind == LosslessTokenKind::Word && tk.text.ends_with(':'))
+            .count();
+        let statement = &significant[label_count..];
+
+        for (i, label) in significant[..label_count].iter().enumerate() {
+            let is_last = i + 1 == label_count;
+            items.push(FormatItem::Label {
+                name: label.text.clone(),
+                comment: if is_last && statement.is_empty() { comment.clone() } else { None },
+            });
+        }
+
+        match statement.split_first() {
+            Some((first, _)) if matches!(first.text.as_str(), ".data" | ".rodata" | ".bss" | ".section" | ".inst") => {
+                items.push(FormatItem::Section {
+                    directive: join_operands(statement),
+                    comment,
+                });
+            }
+            Some((first, rest)) => {
+                items.push(FormatItem::Statement {
+                    mnemonic: first.text.clone(),
+                    operands: join_operands(rest),
+                    comment,
+                });
+            }
+            None => {}
+        }
+    }
+
+    items
+}
+
+/*
+    Rules, in order:
+        - section directives (.data, .rodata, .bss, .section and .inst)
+          start at column 0 with one blank line before them;
+        - labels go alone on their own line at column 0;
+        - statements are indented with one tab, with mnemonics, operands
+          and trailing comments aligned in columns over the whole file;
+        - runs of blank lines collapse into one, and the file ends with
+          exactly one newline.
+ */
+pub fn format_source(source: &str) -> String {
+    let items = split_into_items(source);
+
+    let mnemonic_width = items
+        .iter()
+        .filter_map(|item| match item {
+            FormatItem::Statement { mnemonic, .. } => Some(mnemonic.len()),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    let operands_width = items
+        .iter()
+        .filter_map(|item| match item {
+            FormatItem::Statement { operands, .. } => Some(operands.len()),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+
+    let mut lines: Vec<String> = Vec::new();
+    for item in items.iter() {
+        match item {
+            FormatItem::Blank => {
+                if matches!(lines.last(), Some(last) if !last.is_empty()) {
+                    lines.push(String::new());
+                }
+            }
+            FormatItem::Comment { text, indented } => {
+                if *indented {
+                    lines.push(format!("\t{}", text));
+                } else {
+                    lines.push(text.clone());
+                }
+            }
+            FormatItem::Section { directive, comment } => {
+                if matches!(lines.last(), Some(last) if !last.is_empty()) {
+                    lines.push(String::new());
+                }
+                match comment {
+                    Some(comment) => lines.push(format!("{} {}", directive, comment)),
+                    None => lines.push(directive.clone()),
+                }
+            }
+            FormatItem::Label { name, comment } => {
+                match comment {
+                    Some(comment) => lines.push(format!("{} {}", name, comment)),
+                    None => lines.push(name.clone()),
+                }
+            }
+            FormatItem::Statement { mnemonic, operands, comment } => {
+                let line = match comment {
+                    Some(comment) => format!(
+                        "\t{:<mw$} {:<ow$} {}",
+                        mnemonic,
+                        operands,
+                        comment,
+                        mw = mnemonic_width,
+                        ow = operands_width
+                    ),
+                    None => format!("\t{:<mw$} {}", mnemonic, operands, mw = mnemonic_width),
+                };
+                lines.push(line.trim_end().to_string());
+            }
+        }
+    }
+
+    while matches!(lines.last(), Some(last) if last.is_empty()) {
+        lines.pop();
+    }
+
+    let mut formatted = lines.join("\n");
+    formatted.push('\n');
+    formatted
+}
+
+pub trait Formattable {
+    fn format_files(&mut self, file_paths: &[String], check: bool);
+}
+
+impl Formattable for Moonlight {
+    fn format_files(&mut self, file_paths: &[String], check: bool) {
+        let mut unformatted_files: Vec<&String> = Vec::new();
+
+        for file_path in file_paths.iter() {
+            let source = match std::fs::read_to_string(file_path) {
+                Ok(content) => content,
+                Err(_) => self.exit_with_error(&format!("The file {} does not exist or could not be read", file_path)),
+            };
+
+            let formatted = format_source(&source);
+            if formatted == source {
+                continue;
+            }
+
+            if check {
+                unformatted_files.push(file_path);
+            } else if std::fs::write(file_path, formatted).is_err() {
+                self.exit_with_error(&format!("The file {} could not be written", file_path));
+            }
+        }
+
+        if !unformatted_files.is_empty() {
+            for file_path in unformatted_files.iter() {
+                println!("{} is not formatted", file_path);
+            }
+            std::process::exit(1);
+        }
+    }
+}
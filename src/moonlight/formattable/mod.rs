@@ -0,0 +1,3 @@
+pub mod formattable;
+
+pub use formattable::*;
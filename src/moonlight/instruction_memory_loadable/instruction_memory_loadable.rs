@@ -0,0 +1,142 @@
+use crate::moonlight::debugable::*;
+use crate::moonlight::parseable::*;
+use crate::moonlight::utils::*;
+use crate::moonlight::Moonlight;
+
+fn operand_value(ptk: &PositionedToken, field: &OperandField, spec: &InstructionSpec) -> Result<i32, (String, Position)> {
+    match (&field.kind, &ptk.token) {
+        (OperandKind::Accumulator, Token::Accumulator(ac)) => Ok(ac.index() as i32),
+        (OperandKind::Register, Token::Register(r)) => Ok(r.index() as i32),
+        (OperandKind::Immediate, Token::Number(number)) => {
+            let value = if spec.signed_immediate {
+                number.to_i16().map(|v| v as i32)
+            } else {
+                number.to_u16().map(|v| v as i32)
+            };
+            value.map_err(|e| (e, ptk.position))
+        }
+        _ => unreachable!(),
+    }
+}
+
+pub fn encode_instruction(instr_camp: &InstrCamp) -> Result<u16, (String, Position)> {
+    let spec = match instr_camp.instruction.token {
+        Token::Instruction(ref instr) => instr.spec(),
+        Token::PseudoInstruction(_) => {
+            return Err((
+                "Pseudo instructions can not be encoded yet.".to_string(),
+                instr_camp.instruction.position,
+            ));
+        }
+        Token::Directive(Directive::Raw) => {
+            // A raw word is written as is, given either as a signed or as an unsigned 16-bit number
+            return match instr_camp.arg {
+                InstrArg::Number { ref number } => match number.token {
+                    Token::Number(ref n) => n
+                        .to_u16()
+                        .or_else(|_| n.to_i16().map(|v| v as u16))
+                        .map_err(|_| ("Raw instruction words must be in -32768..=65535".to_string(), number.position)),
+                    _ => unreachable!(),
+                },
+                _ => unreachable!(),
+            };
+        }
+        _ => unreachable!(),
+    };
+
+    let operand_tokens: Vec<&PositionedToken> = match instr_camp.arg {
+        InstrArg::Empty => vec![],
+        InstrArg::AcRR { ref ac, ref r1, ref r2 } => vec![ac, r1, r2],
+        InstrArg::AcR { ref ac, ref r } => vec![ac, r],
+        InstrArg::R { ref r } => vec![r],
+        InstrArg::AcRNumber { ref ac, ref r, ref number } => vec![ac, r, number],
+        InstrArg::Ac { ref ac } => vec![ac],
+        InstrArg::AcNumber { ref ac, ref number } => vec![ac, number],
+        InstrArg::Number { ref number } => vec![number],
+        _ => unreachable!(),
+    };
+
+    let mut operands = Vec::new();
+    for (ptk, field) in operand_tokens.iter().zip(spec.format.fields().iter()) {
+        operands.push(operand_value(ptk, field, spec)?);
+    }
+
+    // Range errors point at the last operand, which is the only immediate of every format
+    let error_position = match operand_tokens.last() {
+        Some(ptk) => ptk.position,
+        None => instr_camp.instruction.position,
+    };
+    spec.encode(&operands).map_err(|e| (e, error_position))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Bin, // Big-endian 16-bit words, the format read by disasm
+    Hex, // One word per line as four hex digits, readable by $readmemh
+    SvRom, // SystemVerilog ROM and RAM modules, see rom_generatable
+    VhdlRom, // VHDL ROM and RAM entities, see rom_generatable
+}
+
+impl ImageFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bin" => Some(ImageFormat::Bin),
+            "hex" => Some(ImageFormat::Hex),
+            "sv-rom" => Some(ImageFormat::SvRom),
+            "vhdl-rom" => Some(ImageFormat::VhdlRom),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Bin => "bin",
+            ImageFormat::Hex => "hex",
+            ImageFormat::SvRom => "sv",
+            ImageFormat::VhdlRom => "vhd",
+        }
+    }
+}
+
+pub fn write_image(path: &str, words: &[u16], format: ImageFormat) -> Result<(), String> {
+    let bytes = match format {
+        ImageFormat::Bin => words.iter().flat_map(|word| word.to_be_bytes()).collect::<Vec<u8>>(),
+        ImageFormat::Hex => words
+            .iter()
+            .map(|word| format!("{:04x}\n", word))
+            .collect::<String>()
+            .into_bytes(),
+        ImageFormat::SvRom | ImageFormat::VhdlRom => {
+            return Err("ROM modules are generated from the assembled program, not from raw words".to_string());
+        }
+    };
+
+    std::fs::write(path, bytes).map_err(|_| format!("The file {} could not be written", path))
+}
+
+pub trait InstructionMemoryLoadable {
+    fn setup_instruction_memory_from(&mut self, ast: &Ast) -> Result<(), Diagnostic>;
+}
+
+impl InstructionMemoryLoadable for Moonlight {
+    fn setup_instruction_memory_from(&mut self, ast: &Ast) -> Result<(), Diagnostic> {
+        self.instruction_memory.clear();
+        if let Some(instr_camp) = ast.instr_field.get(self.machine_profile.instruction_memory_size) {
+            return Err(Diagnostic::positional_error(
+                &format!(
+                    "The instruction field has {} instructions, more than the {} words of instruction memory",
+                    ast.instr_field.len(),
+                    self.machine_profile.instruction_memory_size
+                ),
+                instr_camp.instruction.position,
+            ));
+        }
+        for instr_camp in ast.instr_field.iter() {
+            match encode_instruction(instr_camp) {
+                Ok(word) => self.instruction_memory.push(word),
+                Err((e, position)) => return Err(Diagnostic::positional_error(e.as_str(), position)),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+pub mod instruction_memory_loadable;
+
+pub use instruction_memory_loadable::*;
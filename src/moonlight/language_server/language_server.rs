@@ -0,0 +1,538 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::{BufRead, Write};
+
+use serde_json::{json, Value};
+
+use crate::moonlight::lintable::*;
+use crate::moonlight::scanneable::*;
+use crate::moonlight::utils::*;
+use crate::moonlight::Moonlight;
+
+
+const DIAGNOSTIC_ERROR: u32 = 1;
+const DIAGNOSTIC_WARNING: u32 = 2;
+
+const COMPLETION_FUNCTION: u32 = 3;
+const COMPLETION_VARIABLE: u32 = 6;
+const COMPLETION_KEYWORD: u32 = 14;
+const COMPLETION_REFERENCE: u32 = 18;
+
+fn uri_to_path(uri: &str) -> String {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+
+    // Undo the percent encoding editors apply to paths
+    let bytes = path.as_bytes();
+    let mut decoded: Vec<u8> = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%'
+            && index + 2 < bytes.len()
+            && let Ok(hex) = std::str::from_utf8(&bytes[index + 1..index + 3])
+            && let Ok(byte) = u8::from_str_radix(hex, 16)
+        {
+            decoded.push(byte);
+            index += 3;
+            continue;
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// Absolute form of a path, the same for every way a file is named
+fn normalize_path(path: &str) -> String {
+    match std::fs::canonicalize(path) {
+        Ok(absolute) => absolute.to_string_lossy().to_string(),
+        Err(_) => match std::env::current_dir() {
+            Ok(current_dir) => current_dir.join(path).to_string_lossy().to_string(),
+            Err(_) => path.to_string(),
+        },
+    }
+}
+
+fn path_to_uri(path: &str) -> String {
+    let encoded: String = normalize_path(path)
+        .chars()
+        .map(|ch| match ch {
+            ' ' => "%20".to_string(),
+            '#' => "%23".to_string(),
+            _ => ch.to_string(),
+        })
+        .collect();
+    format!("file://{}", encoded)
+}
+
+fn read_message(reader: &mut impl BufRead) -> Option<Value> {
+    let mut content_length: Option<usize> = None;
+    loop {
+        let mut header = String::new();
+        match reader.read_line(&mut header) {
+            Ok(0) | Err(_) => return None,
+            Ok(_) => {}
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(length) = header.strip_prefix("Content-Length:") {
+            content_length = length.trim().parse().ok();
+        }
+    }
+
+    let mut content = vec![0; content_length?];
+    reader.read_exact(&mut content).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) {
+    let content = message.to_string();
+    let _ = write!(writer, "Content-Length: {}\r\n\r\n{}", content.len(), content);
+    let _ = writer.flush();
+}
+
+pub struct LanguageServer {
+    documents: HashMap<String, String>, // Open documents by path
+    published_uris: HashSet<String>,
+}
+
+impl Default for LanguageServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LanguageServer {
+    pub fn new() -> Self {
+        LanguageServer {
+            documents: HashMap::new(),
+            published_uris: HashSet::new(),
+        }
+    }
+
+    pub fn run(&mut self) {
+        let stdin = std::io::stdin();
+        let mut reader = stdin.lock();
+        let stdout = std::io::stdout();
+        let mut writer = stdout.lock();
+
+        while let Some(message) = read_message(&mut reader) {
+            let method = message["method"].as_str().unwrap_or("").to_string();
+            let params = &message["params"];
+
+            if method == "exit" {
+                break;
+            }
+
+            let result = match method.as_str() {
+                "initialize" => {
+                    if let Some(root) = params["rootUri"].as_str() {
+                        // .include paths are resolved from the workspace root
+                        let _ = std::env::set_current_dir(uri_to_path(root));
+                    }
+                    Some(json!({
+                        "capabilities": {
+                            "textDocumentSync": 1,
+                            "definitionProvider": true,
+                            "referencesProvider": true,
+                            "hoverProvider": true,
+                            "completionProvider": {
+                                "triggerCharacters": ["$", "&", "_", "."],
+                            },
+                        },
+                        "serverInfo": {
+                            "name": "moonlight",
+                        },
+                    }))
+                }
+                "shutdown" => Some(Value::Null),
+                "textDocument/didOpen" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                    let text = params["textDocument"]["text"].as_str().unwrap_or("");
+                    self.documents.insert(normalize_path(&uri_to_path(uri)), text.to_string());
+                    self.publish_diagnostics(&mut writer, uri);
+                    None
+                }
+                "textDocument/didChange" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                    // Full synchronization, the last change holds the whole text
+                    if let Some(text) = params["contentChanges"]
+                        .as_array()
+                        .and_then(|changes| changes.last())
+                        .and_then(|change| change["text"].as_str())
+                    {
+                        self.documents.insert(normalize_path(&uri_to_path(uri)), text.to_string());
+                    }
+                    self.publish_diagnostics(&mut writer, uri);
+                    None
+                }
+                "textDocument/didSave" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                    self.publish_diagnostics(&mut writer, uri);
+                    None
+                }
+                "textDocument/didClose" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                    self.documents.remove(&normalize_path(&uri_to_path(uri)));
+                    None
+                }
+                "textDocument/definition" => Some(self.definition(params)),
+                "textDocument/references" => Some(self.references(params)),
+                "textDocument/hover" => Some(self.hover(params)),
+                "textDocument/completion" => Some(self.completion(params)),
+                _ => None,
+            };
+
+            // Notifications have no id and get no response
+            if let Some(id) = message.get("id") {
+                let response = match result {
+                    Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    None => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": -32601, "message": format!("Method not found: {}", method) },
+                    }),
+                };
+                write_message(&mut writer, &response);
+            }
+        }
+    }
+
+    fn read_source(&self, path: &str) -> Option<String> {
+        match self.documents.get(&normalize_path(path)) {
+            Some(text) => Some(text.clone()),
+            None => FileSystemSourceProvider.read_source(path).ok(),
+        }
+    }
+
+    fn lossless_tokens(&self, path: &str) -> Vec<LosslessToken> {
+        match self.read_source(path) {
+            Some(source) => scan_string_and_generate_lossless_tokens(&source, 0),
+            None => Vec::new(),
+        }
+    }
+
+    /*
+        Files reachable through .include from the given path and from
+        every open document, so a label can be found from any file that
+        shares it.
+     */
+    fn related_files(&self, path: &str) -> Vec<String> {
+        let mut pending: Vec<String> = vec![normalize_path(path)];
+        pending.extend(self.documents.keys().cloned());
+
+        let mut visited: Vec<String> = Vec::new();
+        while let Some(current) = pending.pop().map(|file| normalize_path(&file)) {
+            if visited.contains(&current) {
+                continue;
+            }
+            let significant: Vec<LosslessToken> = self
+                .lossless_tokens(&current)
+                .into_iter()
+                .filter(|tk| !tk.is_trivia())
+                .collect();
+            for pair in significant.windows(2) {
+                if pair[0].text == ".include" && pair[1].kind == LosslessTokenKind::StringLiteral {
+                    pending.push(pair[1].text.trim_matches('"').to_string());
+                }
+            }
+            visited.push(current);
+        }
+        visited
+    }
+
+    fn word_at(&self, params: &Value) -> Option<(String, LosslessToken)> {
+        let path = uri_to_path(params["textDocument"]["uri"].as_str()?);
+        let line = params["position"]["line"].as_u64()? as u32 + 1;
+        let character = params["position"]["character"].as_u64()? as u32 + 1;
+
+        let token = self.lossless_tokens(&path).into_iter().find(|tk| {
+            let column = tk.position.column.unwrap_or(1);
+            !tk.is_trivia()
+                && tk.position.line == line
+                && column <= character
+                && character <= column + tk.text.chars().count() as u32
+        })?;
+        Some((path, token))
+    }
+
+    fn label_occurrences(&self, path: &str, label: &str, include_references: bool) -> Vec<Value> {
+        let mut locations: Vec<Value> = Vec::new();
+        for file in self.related_files(path) {
+            for tk in self.lossless_tokens(&file).iter() {
+                let is_declaration = tk.text.strip_suffix(':') == Some(label);
+                let is_reference = tk.text == label;
+                if is_declaration || (include_references && is_reference) {
+                    locations.push(location(&file, tk));
+                }
+            }
+        }
+        locations
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        match self.word_at(params) {
+            Some((path, token)) if token.text.starts_with('_') => {
+                let label = token.text.trim_end_matches(':');
+                Value::Array(self.label_occurrences(&path, label, false))
+            }
+            _ => Value::Null,
+        }
+    }
+
+    fn references(&self, params: &Value) -> Value {
+        match self.word_at(params) {
+            Some((path, token)) if token.text.starts_with('_') => {
+                let label = token.text.trim_end_matches(':');
+                Value::Array(self.label_occurrences(&path, label, true))
+            }
+            _ => Value::Null,
+        }
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let (path, token) = match self.word_at(params) {
+            Some(found) => found,
+            None => return Value::Null,
+        };
+
+        let contents = match Token::from_string(&token.text) {
+            Ok(Token::Instruction(instruction)) => {
+                let spec = instruction.spec();
+                let operands: String = spec.operand_descriptions()
+                    .iter()
+                    .map(|description| format!("\n- {}", description))
+                    .collect();
+                format!(
+                    "```\n{} {}\n```\n{}\n{}\n\nOpcode `0b{:06b}`, format `{:?}`, provisional layout `{}`",
+                    spec.mnemonic,
+                    spec.format.syntax(),
+                    spec.summary,
+                    operands,
+                    spec.opcode,
+                    spec.format,
+                    spec.format.layout()
+                )
+            }
+            Ok(Token::PseudoInstruction(pseudo_instruction)) => format!(
+                "```\n{} {}\n```\n{}\n\nPseudo instruction",
+                pseudo_instruction.mnemonic(),
+                pseudo_instruction.syntax(),
+                pseudo_instruction.summary()
+            ),
+            Ok(Token::Directive(directive)) => format!(
+                "```\n{} {}\n```\n{}",
+                directive.name(),
+                directive.syntax(),
+                directive.summary()
+            ),
+            Ok(Token::Register(Register::Rf14)) => "Register `$14`, the stack pointer".to_string(),
+            Ok(Token::Register(Register::Rf15)) => "Register `$15`, the link register".to_string(),
+            Ok(Token::Register(register)) => format!("Register `{}`", register.name()),
+            Ok(Token::Accumulator(accumulator)) => format!("Accumulator `{}`", accumulator.name()),
+            Ok(Token::LabelDeclaration(label)) | Ok(Token::LabelReference(label)) => {
+                // Shows the line that declares the label
+                let mut declarations: Vec<String> = Vec::new();
+                for file in self.related_files(&path) {
+                    let source = self.read_source(&file).unwrap_or_default();
+                    for tk in scan_string_and_generate_lossless_tokens(&source, 0).iter() {
+                        if tk.text.strip_suffix(':') == Some(label.as_str()) {
+                            let line = source.lines().nth(tk.position.line as usize - 1).unwrap_or("");
+                            declarations.push(format!("```\n{}\n```\n{}:{}", line.trim(), file, tk.position.line));
+                        }
+                    }
+                }
+                if declarations.is_empty() {
+                    format!("Label `{}` is not declared", label)
+                } else {
+                    declarations.join("\n\n")
+                }
+            }
+            _ => return Value::Null,
+        };
+
+        json!({
+            "contents": { "kind": "markdown", "value": contents },
+            "range": range(&token),
+        })
+    }
+
+    fn completion(&self, params: &Value) -> Value {
+        let mut items: Vec<Value> = Vec::new();
+
+        for spec in ISA.iter() {
+            items.push(json!({
+                "label": spec.mnemonic,
+                "kind": COMPLETION_KEYWORD,
+                "detail": format!("{} {}", spec.mnemonic, spec.format.syntax()),
+                "documentation": spec.summary,
+            }));
+        }
+        for pseudo_instruction in PSEUDO_INSTRUCTIONS.iter() {
+            items.push(json!({
+                "label": pseudo_instruction.mnemonic(),
+                "kind": COMPLETION_FUNCTION,
+                "detail": format!("{} {}", pseudo_instruction.mnemonic(), pseudo_instruction.syntax()),
+                "documentation": pseudo_instruction.summary(),
+            }));
+        }
+        for directive in DIRECTIVES.iter() {
+            items.push(json!({
+                "label": directive.name(),
+                "kind": COMPLETION_KEYWORD,
+                "detail": format!("{} {}", directive.name(), directive.syntax()),
+                "documentation": directive.summary(),
+            }));
+        }
+        for index in 0..16 {
+            if let Some(register) = Register::from_index(index) {
+                items.push(json!({ "label": register.name(), "kind": COMPLETION_VARIABLE }));
+            }
+        }
+        for index in 0..4 {
+            if let Some(accumulator) = Accumulator::from_index(index) {
+                items.push(json!({ "label": accumulator.name(), "kind": COMPLETION_VARIABLE }));
+            }
+        }
+
+        if let Some(uri) = params["textDocument"]["uri"].as_str() {
+            let mut labels: Vec<String> = Vec::new();
+            for file in self.related_files(&uri_to_path(uri)) {
+                for tk in self.lossless_tokens(&file).iter() {
+                    if let Some(label) = tk.text.strip_suffix(':')
+                        && label.starts_with('_')
+                        && !labels.iter().any(|known| known == label)
+                    {
+                        labels.push(label.to_string());
+                    }
+                }
+            }
+            for label in labels {
+                items.push(json!({ "label": label, "kind": COMPLETION_REFERENCE }));
+            }
+        }
+
+        Value::Array(items)
+    }
+
+    fn publish_diagnostics(&mut self, writer: &mut impl Write, uri: &str) {
+        let path = uri_to_path(uri);
+
+        // The scanner looks files up by the path written in .include, relative to the workspace root
+        let mut ml = Moonlight::new();
+        let mut source_provider = InMemorySourceProvider::over(&FileSystemSourceProvider);
+        let current_dir = std::env::current_dir()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .unwrap_or_default();
+        for (document_path, text) in self.documents.iter() {
+            source_provider.insert(document_path, text);
+            if let Some(relative_path) = document_path.strip_prefix(&format!("{}/", current_dir)) {
+                source_provider.insert(relative_path, text);
+            }
+        }
+        // The notes and infos of the pipeline are not worth a diagnostic
+        let result = ml
+            .check_sources(&path, &source_provider, &mut Vec::new())
+            .map(|ast| ml.lint(&ast));
+
+        let mut diagnostics_by_uri: HashMap<String, Vec<Value>> = HashMap::new();
+        for file in ml.file_table.values() {
+            diagnostics_by_uri.insert(path_to_uri(file), Vec::new());
+        }
+
+        match result {
+            Ok(warnings) => {
+                for warning in warnings.iter() {
+                    let file = ml.get_file_name(warning.position.file);
+                    diagnostics_by_uri
+                        .entry(path_to_uri(&file))
+                        .or_default()
+                        .push(json!({
+                            "range": self.position_range(&file, warning.position),
+                            "severity": DIAGNOSTIC_WARNING,
+                            "code": warning.lint.name(),
+                            "source": "moonlight",
+                            "message": warning.message,
+                        }));
+                }
+            }
+            Err(error) => {
+                // Errors without a position are reported on the first line of the document
+                let (file, position) = match error.position {
+                    Some(position) => (ml.get_file_name(position.file), position),
+                    None => (path.clone(), Position::new(0, 1, None)),
+                };
+                diagnostics_by_uri
+                    .entry(path_to_uri(&file))
+                    .or_default()
+                    .push(json!({
+                        "range": self.position_range(&file, position),
+                        "severity": DIAGNOSTIC_ERROR,
+                        "source": "moonlight",
+                        "message": error.message,
+                    }));
+            }
+        }
+
+        // Clears the diagnostics of files that are no longer reached
+        for stale_uri in self.published_uris.iter() {
+            diagnostics_by_uri.entry(stale_uri.clone()).or_default();
+        }
+        diagnostics_by_uri.entry(path_to_uri(&path)).or_default();
+
+        self.published_uris.clear();
+        for (uri, diagnostics) in diagnostics_by_uri.into_iter() {
+            if !diagnostics.is_empty() {
+                self.published_uris.insert(uri.clone());
+            }
+            write_message(writer, &json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/publishDiagnostics",
+                "params": { "uri": uri, "diagnostics": diagnostics },
+            }));
+        }
+    }
+
+    /*
+        Tokens on indented lines have no column, so the diagnostic then
+        covers the whole line.
+     */
+    fn position_range(&self, file: &str, position: Position) -> Value {
+        let line = position.line.saturating_sub(1);
+        if let Some(column) = position.column {
+            let token = self
+                .lossless_tokens(file)
+                .into_iter()
+                .find(|tk| tk.position.line == position.line && tk.position.column == Some(column));
+            if let Some(token) = token {
+                return range(&token);
+            }
+        }
+
+        let line_length = self
+            .read_source(file)
+            .and_then(|source| source.lines().nth(line as usize).map(|text| text.chars().count()))
+            .unwrap_or(0);
+        json!({
+            "start": { "line": line, "character": 0 },
+            "end": { "line": line, "character": line_length },
+        })
+    }
+}
+
+fn range(token: &LosslessToken) -> Value {
+    let line = token.position.line.saturating_sub(1);
+    let start = token.position.column.unwrap_or(1).saturating_sub(1);
+    json!({
+        "start": { "line": line, "character": start },
+        "end": { "line": line, "character": start + token.text.chars().count() as u32 },
+    })
+}
+
+fn location(file: &str, token: &LosslessToken) -> Value {
+    json!({
+        "uri": path_to_uri(file),
+        "range": range(token),
+    })
+}
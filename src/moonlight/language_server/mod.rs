@@ -0,0 +1,3 @@
+pub mod language_server;
+
+pub use language_server::*;
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::moonlight::debugable::*;
+use crate::moonlight::parseable::*;
+use crate::moonlight::utils::*;
+use crate::moonlight::Moonlight;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    UnusedLabel,
+    UnreachableCode,
+    ByteAccessedAsWord,
+    CallWithoutRet,
+    FallsThroughEnd,
+    StoreToReadOnly,
+}
+
+pub const LINTS: [Lint; 6] = [
+    Lint::UnusedLabel,
+    Lint::UnreachableCode,
+    Lint::ByteAccessedAsWord,
+    Lint::CallWithoutRet,
+    Lint::FallsThroughEnd,
+    Lint::StoreToReadOnly,
+];
+
+impl Lint {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedLabel => "unused_label",
+            Lint::UnreachableCode => "unreachable_code",
+            Lint::ByteAccessedAsWord => "byte_accessed_as_word",
+            Lint::CallWithoutRet => "call_without_ret",
+            Lint::FallsThroughEnd => "falls_through_end",
+            Lint::StoreToReadOnly => "store_to_read_only",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        LINTS.iter().find(|lint| lint.name() == name).copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+#[derive(Debug, Clone)]
+pub struct LintWarning {
+    pub lint: Lint,
+    pub message: String,
+    pub position: Position,
+}
+
+fn label_name(ptk: &PositionedToken) -> &str {
+    match ptk.token {
+        Token::LabelDeclaration(ref label) | Token::LabelReference(ref label) => label,
+        _ => unreachable!(),
+    }
+}
+
+fn referenced_label(instr_camp: &InstrCamp) -> Option<&PositionedToken> {
+    match instr_camp.arg {
+        InstrArg::Jump { ref target } | InstrArg::Call { ref target } => {
+            match target.token {
+                Token::LabelReference(_) => Some(target),
+                _ => None,
+            }
+        }
+        InstrArg::LwSw { ref label_reference, .. } => Some(label_reference),
+        _ => None,
+    }
+}
+
+/*
+    Control never reaches the next instruction after these, unless
+    something jumps to it.
+ */
+fn is_unconditional_transfer(instr_camp: &InstrCamp) -> bool {
+    matches!(
+        instr_camp.instruction.token,
+        Token::PseudoInstruction(PseudoInstruction::Jump)
+        | Token::PseudoInstruction(PseudoInstruction::Ret)
+        | Token::Instruction(Instruction::Ja)
+        | Token::Instruction(Instruction::Jr)
+    )
+}
+
+pub trait Lintable {
+    fn lint(&self, ast: &Ast) -> Vec<LintWarning>;
+    fn report_lints(&self, ast: &Ast, levels: &HashMap<Lint, LintLevel>);
+}
+
+impl Lintable for Moonlight {
+    fn lint(&self, ast: &Ast) -> Vec<LintWarning> {
+        let mut warnings: Vec<LintWarning> = Vec::new();
+
+        let mut instr_label_indexes: HashMap<&str, usize> = HashMap::new();
+        for (index, instr_camp) in ast.instr_field.iter().enumerate() {
+            for label in instr_camp.label_declarations.iter() {
+                instr_label_indexes.insert(label_name(label), index);
+            }
+        }
+
+        let mut data_label_directives: HashMap<&str, &Token> = HashMap::new();
+        let mut data_label_sections: HashMap<&str, &str> = HashMap::new();
+        for data_camp in ast.data_field.iter() {
+            for label in data_camp.label_declarations.iter() {
+                data_label_directives.insert(label_name(label), &data_camp.directive.token);
+                data_label_sections.insert(label_name(label), &data_camp.section);
+            }
+        }
+
+        ////////////////////////
+        // UNUSED LABEL
+        ////////////////////////
+        let referenced_labels: HashSet<&str> = ast.instr_field
+            .iter()
+            .filter_map(referenced_label)
+            .map(label_name)
+            .collect();
+
+        let declared_labels = ast.data_field
+            .iter()
+            .flat_map(|data_camp| data_camp.label_declarations.iter())
+            .chain(ast.instr_field.iter().flat_map(|instr_camp| instr_camp.label_declarations.iter()));
+        for label in declared_labels {
+            // The entry label is used by whoever starts the program
+            let is_entry_label = self.entry_label.as_deref() == Some(label_name(label));
+            if !referenced_labels.contains(label_name(label)) && !is_entry_label {
+                warnings.push(LintWarning {
+                    lint: Lint::UnusedLabel,
+                    message: format!("Label {} is declared but never referenced", label_name(label)),
+                    position: label.position,
+                });
+            }
+        }
+
+        ////////////////////////
+        // UNREACHABLE CODE
+        ////////////////////////
+        // A numeric branch may land anywhere, so any instruction could be reachable
+        let has_numeric_targets = ast.instr_field.iter().any(|instr_camp| instr_camp.numeric_target().is_some());
+        for pair in ast.instr_field.windows(2) {
+            if is_unconditional_transfer(&pair[0]) && pair[1].label_declarations.is_empty() && !has_numeric_targets {
+                warnings.push(LintWarning {
+                    lint: Lint::UnreachableCode,
+                    message: "Unreachable instruction after an unconditional jump".to_string(),
+                    position: pair[1].instruction.position,
+                });
+            }
+        }
+
+        ////////////////////////////
+        // BYTE ACCESSED AS WORD
+        ////////////////////////////
+        for instr_camp in ast.instr_field.iter() {
+            if let InstrArg::LwSw { ref label_reference, .. } = instr_camp.arg
+                && let Some(Token::Directive(Directive::Byte)) = data_label_directives.get(label_name(label_reference))
+            {
+                warnings.push(LintWarning {
+                    lint: Lint::ByteAccessedAsWord,
+                    message: format!("Label {} is declared with .byte but accessed as a word", label_name(label_reference)),
+                    position: label_reference.position,
+                });
+            }
+        }
+
+        ////////////////////////////
+        // STORE TO READ ONLY
+        ////////////////////////////
+        for instr_camp in ast.instr_field.iter() {
+            if let Token::PseudoInstruction(PseudoInstruction::Sw) = instr_camp.instruction.token
+                && let InstrArg::LwSw { ref label_reference, .. } = instr_camp.arg
+                && let Some(section) = data_label_sections.get(label_name(label_reference))
+                && self.sections.iter().any(|placement| placement.name == *section && placement.read_only)
+            {
+                warnings.push(LintWarning {
+                    lint: Lint::StoreToReadOnly,
+                    message: format!("Label {} is in the read-only section {} but is stored to", label_name(label_reference), section),
+                    position: label_reference.position,
+                });
+            }
+        }
+
+        ////////////////////////
+        // CALL WITHOUT RET
+        ////////////////////////
+        for instr_camp in ast.instr_field.iter() {
+            let target = match instr_camp.arg {
+                InstrArg::Call { ref target } => target,
+                _ => continue,
+            };
+            let start = match instr_label_indexes.get(label_name(target)) {
+                Some(start) => *start,
+                None => continue,
+            };
+
+            /*
+                Follows the fall through and the jumps to labels. Register
+                jumps and numeric targets go somewhere unknown, so they
+                count as possibly reaching a ret.
+             */
+            let mut pending = vec![start];
+            let mut visited: HashSet<usize> = HashSet::new();
+            let mut may_return = false;
+            while let Some(index) = pending.pop() {
+                if may_return || !visited.insert(index) {
+                    continue;
+                }
+                let current = match ast.instr_field.get(index) {
+                    Some(current) => current,
+                    None => continue,
+                };
+                match current.instruction.token {
+                    Token::PseudoInstruction(PseudoInstruction::Ret)
+                    | Token::Instruction(Instruction::Ja)
+                    | Token::Instruction(Instruction::Jr) => may_return = true,
+                    Token::PseudoInstruction(PseudoInstruction::Jump) => {
+                        match referenced_label(current).and_then(|label| instr_label_indexes.get(label_name(label))) {
+                            Some(next) => pending.push(*next),
+                            None => may_return = true,
+                        }
+                    }
+                    _ if current.numeric_target().is_some() => may_return = true,
+                    _ => pending.push(index + 1),
+                }
+            }
+
+            if !may_return {
+                warnings.push(LintWarning {
+                    lint: Lint::CallWithoutRet,
+                    message: format!("Called label {} never reaches a ret", label_name(target)),
+                    position: target.position,
+                });
+            }
+        }
+
+        ////////////////////////
+        // FALLS THROUGH END
+        ////////////////////////
+        if let Some(last) = ast.instr_field.last()
+            && !is_unconditional_transfer(last)
+        {
+            warnings.push(LintWarning {
+                lint: Lint::FallsThroughEnd,
+                message: "Execution falls through the end of the instruction field".to_string(),
+                position: last.instruction.position,
+            });
+        }
+
+        warnings.sort_by_key(|warning| (warning.position.file, warning.position.line, warning.position.column));
+        warnings
+    }
+
+    fn report_lints(&self, ast: &Ast, levels: &HashMap<Lint, LintLevel>) {
+        let mut denied_lints: usize = 0;
+        for warning in self.lint(ast).iter() {
+            let message = format!("{} [{}]", warning.message, warning.lint.name());
+            match levels.get(&warning.lint).copied().unwrap_or(LintLevel::Warn) {
+                LintLevel::Allow => {}
+                LintLevel::Warn => self.print_positional_warning(&message, warning.position),
+                LintLevel::Deny => {
+                    self.print_positional_error(&message, warning.position);
+                    denied_lints += 1;
+                }
+            }
+        }
+
+        if denied_lints > 0 {
+            self.exit_with_error(&format!("Denied lints reported: {}", denied_lints));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moonlight::scanneable::*;
+    use crate::moonlight::section_layoutable::*;
+
+    fn lints(source: &str) -> Vec<Lint> {
+        let mut ml = Moonlight::new();
+        let mut source_provider = InMemorySourceProvider::new();
+        source_provider.insert("test.asm", source);
+        let tokens = ml.scan("test.asm", &source_provider).unwrap();
+        let mut ast = ml.parse(&tokens).unwrap();
+        ml.lay_out_sections(&mut ast).unwrap();
+        ml.lint(&ast).iter().map(|warning| warning.lint).collect()
+    }
+
+    #[test]
+    fn unused_label() {
+        assert!(lints(".inst\n_start:\n\tjump _unused\n_unused:\n\tjump _start\n_never:\n\tret\n").contains(&Lint::UnusedLabel));
+        assert!(!lints(".inst\n_start:\n\tjump _next\n_next:\n\tjump _start\n").contains(&Lint::UnusedLabel));
+    }
+
+    #[test]
+    fn unreachable_code() {
+        assert!(lints(".inst\n\tret\n\taddi &0, 1\n\tret\n").contains(&Lint::UnreachableCode));
+        assert!(!lints(".inst\n\tjump _next\n_next:\n\taddi &0, 1\n\tret\n").contains(&Lint::UnreachableCode));
+    }
+
+    #[test]
+    fn numeric_branch_targets_may_reach_any_instruction() {
+        assert!(!lints(".inst\n\tbnez &0, 2\n\tret\n\taddi &0, 1\n\tret\n").contains(&Lint::UnreachableCode));
+    }
+
+    #[test]
+    fn byte_accessed_as_word() {
+        assert!(lints(".data\n_x: .byte 1\n.inst\n\tlw &0, _x[0]\n\tret\n").contains(&Lint::ByteAccessedAsWord));
+        assert!(!lints(".data\n_x: .word 1\n.inst\n\tlw &0, _x[0]\n\tret\n").contains(&Lint::ByteAccessedAsWord));
+    }
+
+    #[test]
+    fn store_to_read_only() {
+        assert!(lints(".rodata\n_x: .word 1\n.inst\n\tsw &0, _x[0]\n\tret\n").contains(&Lint::StoreToReadOnly));
+        assert!(!lints(".data\n_x: .word 1\n.inst\n\tsw &0, _x[0]\n\tret\n").contains(&Lint::StoreToReadOnly));
+    }
+
+    #[test]
+    fn call_without_ret() {
+        assert!(lints(".inst\n\tcall _f\n\tret\n_f:\n\tjump _f\n").contains(&Lint::CallWithoutRet));
+        assert!(!lints(".inst\n\tcall _f\n\tret\n_f:\n\taddi &0, 1\n\tret\n").contains(&Lint::CallWithoutRet));
+    }
+
+    #[test]
+    fn numeric_targets_may_return() {
+        assert!(!lints(".inst\n\tcall _f\n\tret\n_f:\n\tjr -1\n").contains(&Lint::CallWithoutRet));
+        assert!(!lints(".inst\n\tcall _f\n\tret\n_f:\n\tbnez &0, 0\n\tjump _f\n").contains(&Lint::CallWithoutRet));
+    }
+
+    #[test]
+    fn falls_through_end() {
+        assert!(lints(".inst\n\taddi &0, 1\n").contains(&Lint::FallsThroughEnd));
+        assert!(!lints(".inst\n\taddi &0, 1\n\tret\n").contains(&Lint::FallsThroughEnd));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod lintable;
+
+pub use lintable::*;
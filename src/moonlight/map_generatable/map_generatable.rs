@@ -0,0 +1,196 @@
+use crate::moonlight::parseable::*;
+use crate::moonlight::utils::*;
+use crate::moonlight::Moonlight;
+
+struct MapSymbol {
+    name: String,
+    address: usize,
+    size: usize,
+    section: String,
+    position: Position,
+}
+
+fn label_name(ptk: &PositionedToken) -> String {
+    match ptk.token {
+        Token::LabelDeclaration(ref label) => label.clone(),
+        _ => unreachable!(),
+    }
+}
+
+fn percentage(used: usize, total: usize) -> f64 {
+    if total == 0 { 0.0 } else { used as f64 * 100.0 / total as f64 }
+}
+
+/*
+    Reads the instruction labels back from a map written by --map, as
+    (label, instruction address) pairs. Data symbols are skipped, since
+    their addresses are in data memory.
+ */
+pub fn read_instruction_symbols(map: &str) -> Result<Vec<(String, usize)>, String> {
+    if !map.lines().any(|line| line == "Symbols") {
+        return Err("Expected a map written by --map, found no Symbols list".to_string());
+    }
+    let lines = map.lines().skip_while(|line| *line != "Symbols").skip(2);
+    let mut symbols: Vec<(String, usize)> = Vec::new();
+    for (line_index, line) in lines.take_while(|line| !line.trim().is_empty()).enumerate() {
+        match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            [address, _size, "inst", name, ..] => match address.strip_prefix("0x").map(|hex| usize::from_str_radix(hex, 16)) {
+                Some(Ok(address)) => symbols.push((name.to_string(), address)),
+                _ => return Err(format!("Invalid address {} in symbol {} of the map", address, line_index + 1)),
+            },
+            [_, _, _, _, ..] => continue,
+            _ => return Err(format!("Expected address, size, section and symbol in symbol {} of the map", line_index + 1)),
+        }
+    }
+    Ok(symbols)
+}
+
+pub trait MapGeneratable {
+    fn generate_map(&self, ast: &Ast) -> String;
+}
+
+impl MapGeneratable for Moonlight {
+    fn generate_map(&self, ast: &Ast) -> String {
+        let mut symbols: Vec<MapSymbol> = Vec::new();
+
+        for data_camp in ast.data_field.iter() {
+            for label in data_camp.label_declarations.iter() {
+                let name = label_name(label);
+                if let Some(address) = self.symbol_table.get(&name) {
+                    symbols.push(MapSymbol {
+                        name,
+                        address: *address as usize,
+                        size: 0,
+                        section: data_camp.section.clone(),
+                        position: label.position,
+                    });
+                }
+            }
+        }
+
+        /*
+            A data symbol covers every declaration up to the next label in
+            its section, or up to the end of the section.
+         */
+        let mut label_addresses: Vec<(String, usize)> = symbols
+            .iter()
+            .map(|symbol| (symbol.section.clone(), symbol.address))
+            .collect();
+        label_addresses.sort();
+        for symbol in symbols.iter_mut() {
+            let section_end = self.sections
+                .iter()
+                .find(|placement| placement.name == symbol.section)
+                .map(|placement| placement.end)
+                .unwrap_or(self.static_data_end);
+            let next_address = label_addresses
+                .iter()
+                .filter(|(section, address)| *section == symbol.section && *address > symbol.address)
+                .map(|(_, address)| *address)
+                .next()
+                .unwrap_or(section_end);
+            symbol.size = next_address - symbol.address;
+        }
+
+        /*
+            Instruction labels are placed at the index of the instruction
+            they precede, one word each, and their size runs until the
+            next labelled instruction.
+         */
+        let labelled_indexes: Vec<usize> = ast.instr_field
+            .iter()
+            .enumerate()
+            .filter(|(_, instr_camp)| !instr_camp.label_declarations.is_empty())
+            .map(|(index, _)| index)
+            .collect();
+        for (i, index) in labelled_indexes.iter().enumerate() {
+            let next_index = labelled_indexes.get(i + 1).copied().unwrap_or(ast.instr_field.len());
+            for label in ast.instr_field[*index].label_declarations.iter() {
+                symbols.push(MapSymbol {
+                    name: label_name(label),
+                    address: *index,
+                    size: next_index - index,
+                    section: "inst".to_string(),
+                    position: label.position,
+                });
+            }
+        }
+
+        let data_size = self.data_memory.len();
+        let instruction_size = self.machine_profile.instruction_memory_size;
+
+        let mut lines: Vec<String> = Vec::new();
+        lines.push(format!("# Moonlight map for {}", self.get_file_name(0)));
+        lines.push(String::new());
+        lines.push("Memory usage".to_string());
+        lines.push(format!(
+            "  data          {:>6} / {:<6} bytes  {:>5.1}%",
+            self.static_data_end,
+            data_size,
+            percentage(self.static_data_end, data_size)
+        ));
+        lines.push(format!(
+            "  inst          {:>6} / {:<6} words  {:>5.1}%",
+            ast.instr_field.len(),
+            instruction_size,
+            percentage(ast.instr_field.len(), instruction_size)
+        ));
+        if let Some((io_start, io_size)) = self.machine_profile.io_window {
+            lines.push(format!("  io window     0x{:04x}..0x{:04x} ({} bytes)", io_start, io_start + io_size, io_size));
+        }
+        lines.push(format!("  stack pointer 0x{:04x}", self.machine_profile.initial_stack_pointer));
+        lines.push(format!("  reset pc      0x{:04x}", self.machine_profile.reset_pc));
+        lines.push(String::new());
+
+        if !self.sections.is_empty() {
+            lines.push("Sections".to_string());
+            for placement in self.sections.iter() {
+                let mut flags: Vec<&str> = Vec::new();
+                if placement.read_only {
+                    flags.push("readonly");
+                }
+                if placement.zero_initialised {
+                    flags.push("zeroed");
+                }
+                lines.push(format!(
+                    "  {:<12} 0x{:04x}..0x{:04x} {:>6} bytes  {}",
+                    placement.name,
+                    placement.start,
+                    placement.end,
+                    placement.end - placement.start,
+                    flags.join(", ")
+                ).trim_end().to_string());
+            }
+            lines.push(String::new());
+        }
+
+        let name_width = symbols.iter().map(|symbol| symbol.name.len()).max().unwrap_or(0).max("symbol".len());
+        lines.push("Symbols".to_string());
+        let section_width = symbols.iter().map(|symbol| symbol.section.len()).max().unwrap_or(0).max("section".len());
+        lines.push(format!(
+            "  {:<7} {:>6} {:<sw$} {:<nw$} defined at",
+            "address", "size", "section", "symbol",
+            sw = section_width,
+            nw = name_width
+        ));
+        // Data symbols in address order, which keeps each section together, then the instruction labels
+        symbols.sort_by_key(|symbol| (symbol.section == "inst", symbol.address, symbol.position.file, symbol.position.line));
+        for symbol in symbols.iter() {
+            lines.push(format!(
+                "  0x{:04x}  {:>6} {:<sw$} {:<nw$} {}:{}",
+                symbol.address,
+                symbol.size,
+                symbol.section,
+                symbol.name,
+                self.get_file_name(symbol.position.file),
+                symbol.position.line,
+                sw = section_width,
+                nw = name_width
+            ));
+        }
+
+        let mut map = lines.join("\n");
+        map.push('\n');
+        map
+    }
+}
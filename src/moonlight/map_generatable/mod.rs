@@ -0,0 +1,3 @@
+pub mod map_generatable;
+
+pub use map_generatable::*;
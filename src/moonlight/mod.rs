@@ -3,7 +3,16 @@ pub mod utils;
 pub mod debugable;
 pub mod scanneable;
 pub mod parseable;
+pub mod section_layoutable;
 pub mod symbol_table_loadable;
 pub mod data_memory_loadable;
+pub mod instruction_memory_loadable;
+pub mod disassemblable;
+pub mod rom_generatable;
+pub mod map_generatable;
+pub mod formattable;
+pub mod lintable;
+pub mod optimizable;
+pub mod language_server;
 
-pub use moonlight::Moonlight;
\ No newline at end of file
+pub use moonlight::{Moonlight, Program, Verbosity};
\ No newline at end of file
@@ -1,25 +1,88 @@
 use std::collections::HashMap;
 use crate::moonlight::data_memory_loadable::*;
+use crate::moonlight::debugable::*;
+use crate::moonlight::instruction_memory_loadable::*;
+use crate::moonlight::map_generatable::*;
+use crate::moonlight::optimizable::*;
 use crate::moonlight::parseable::*;
+use crate::moonlight::rom_generatable::*;
 use crate::moonlight::utils::*;
 use crate::moonlight::scanneable::*;
+use crate::moonlight::section_layoutable::*;
 use crate::moonlight::symbol_table_loadable::*;
 
-pub const DATA_MEMORY_SIZE: usize = 32768;
-
 pub struct Moonlight {
     pub file_table: HashMap<u32, String>,
     pub symbol_table: HashMap<String, u16>,
-    pub data_memory: [u8; DATA_MEMORY_SIZE],
+    pub machine_profile: MachineProfile,
+    pub data_memory: Vec<u8>,
+    pub fill_pattern: FillPattern, // What data memory holds before the program writes to it
+    pub instruction_memory: Vec<u16>,
+    pub static_data_end: usize, // First address after the data laid out by the .data field
+    pub section_layout: Vec<SectionLayoutEntry>, // Where data sections go, the defaults apply when empty
+    pub sections: Vec<SectionPlacement>,
+    pub include_dirs: Vec<String>, // Searched, in order, for included files not found relative to the working directory
+    pub defines: HashMap<String, String>, // Names replaced by their values while scanning
+    pub entry_label: Option<String>,
+    pub layout_path: Option<String>, // Read by check after the sources, so the layout file gets its own file id
+    pub map_path: Option<String>, // Where check writes the symbol map, if anywhere
+    pub optimize_instructions: bool, // Run the peephole pass over the instruction field
+    pub verbosity: Verbosity,
+}
+
+/*
+    What assemble_source hands back: the encoded instructions, the bytes
+    laid out by the .data field, the addresses of the data labels and
+    the notes of each stage.
+ */
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub instruction_memory: Vec<u16>,
+    pub static_data: Vec<u8>,
+    pub symbol_table: HashMap<String, u16>,
+    pub file_table: HashMap<u32, String>,
+    pub diagnostics: Vec<Diagnostic>, // Notes and infos, which the command line would have printed
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    Quiet,
+    #[default]
+    Normal,
+    Verbose,
 }
 
 impl Moonlight {
     pub fn new() -> Self {
+        Self::with_fill_pattern(FillPattern::default())
+    }
+
+    pub fn with_fill_pattern(fill_pattern: FillPattern) -> Self {
+        Self::with_machine_profile(MachineProfile::default(), fill_pattern)
+    }
+
+    pub fn with_machine_profile(machine_profile: MachineProfile, fill_pattern: FillPattern) -> Self {
+        let mut data_memory = vec![0; machine_profile.data_memory_size];
+        fill_pattern.fill(&mut data_memory);
+
         Moonlight {
             file_table: HashMap::new(),
             symbol_table: HashMap::new(),
 
-            data_memory: [7; DATA_MEMORY_SIZE],
+            machine_profile,
+            data_memory,
+            fill_pattern,
+            instruction_memory: Vec::new(),
+            static_data_end: 0,
+            section_layout: Vec::new(),
+            sections: Vec::new(),
+            include_dirs: Vec::new(),
+            defines: HashMap::new(),
+            entry_label: None,
+            layout_path: None,
+            map_path: None,
+            optimize_instructions: false,
+            verbosity: Verbosity::default(),
         }
     }
 
@@ -30,12 +93,183 @@ impl Moonlight {
         }
     }
 
+    /*
+        The stages shared by the command line and by assemble_source.
+        Notes and infos go to diagnostics, the first error comes back as
+        the Err, and nothing is printed or written to disk but the
+        section layout being read.
+     */
+    pub fn check_sources(&mut self, file_path: &str, source_provider: &dyn SourceProvider, diagnostics: &mut Vec<Diagnostic>) -> Result<Ast, Diagnostic> {
+        let tokens: Vec<PositionedToken> = self.scan(file_path, source_provider)?;
+        diagnostics.push(Diagnostic::info(&format!("Scanned {} tokens from {} files", tokens.len(), self.file_table.len())));
+        if let Some(layout_path) = self.layout_path.clone() {
+            self.load_section_layout_from_file(&layout_path)?;
+        }
+        let mut ast = self.parse(&tokens)?;
+        self.lay_out_sections(&mut ast)?;
+        if self.optimize_instructions {
+            match self.optimize(&mut ast) {
+                Some(removed) => diagnostics.push(Diagnostic::note(&format!("The peephole pass removed {} instructions", removed))),
+                None => diagnostics.push(Diagnostic::note(
+                    "The peephole pass was skipped because some branches or jumps have numeric targets, \
+                     and what a numeric target counts from is not defined"
+                )),
+            }
+        }
+        diagnostics.push(Diagnostic::info(&format!(
+            "Parsed {} data declarations and {} instructions",
+            ast.data_field.len(),
+            ast.instr_field.len()
+        )));
+        self.load_symbol_table_from(&ast)?;
+        self.load_data_memory_from(&ast)?;
+        diagnostics.push(Diagnostic::info(&format!("Laid out {} bytes of static data", self.static_data_end)));
+
+        /*
+            The machine starts at the reset PC, so the entry label has to
+            be declared there and the instruction field has to reach it.
+         */
+        let reset_pc = self.machine_profile.reset_pc;
+        if let Some(entry_label) = self.entry_label.clone() {
+            let entry_index = ast.instr_field.iter().position(|instr_camp| {
+                instr_camp.label_declarations.iter().any(|label| {
+                    matches!(label.token, Token::LabelDeclaration(ref name) if *name == entry_label)
+                })
+            });
+            match entry_index {
+                None => return Err(Diagnostic::error(&format!("The entry label {} is not declared in the instruction field", entry_label))),
+                Some(entry_index) if entry_index != reset_pc => return Err(Diagnostic::error(&format!(
+                    "The entry label {} is at 0x{:04x}, but execution starts at the reset PC 0x{:04x}",
+                    entry_label,
+                    entry_index,
+                    reset_pc
+                ))),
+                Some(_) => {}
+            }
+        } else if !ast.instr_field.is_empty() && reset_pc >= ast.instr_field.len() {
+            return Err(Diagnostic::error(&format!(
+                "Execution starts at the reset PC 0x{:04x}, past the last instruction at 0x{:04x}",
+                reset_pc,
+                ast.instr_field.len() - 1
+            )));
+        }
+        Ok(ast)
+    }
+
+    pub fn check(&mut self, file_path: &str) -> Ast {
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        let result = self.check_sources(file_path, &FileSystemSourceProvider, &mut diagnostics);
+        self.print_diagnostics(&diagnostics);
+        let ast = match result {
+            Ok(ast) => ast,
+            Err(error) => self.exit_with_diagnostic(&error),
+        };
+
+        if let Some(map_path) = self.map_path.clone()
+            && std::fs::write(&map_path, self.generate_map(&ast)).is_err()
+        {
+            self.exit_with_error(&format!("The file {} could not be written", map_path));
+        }
+        ast
+    }
+
+    pub fn assemble(&mut self, file_path: &str, output_path: &str, format: ImageFormat) {
+        let ast = self.check(file_path);
+        if let Err(error) = self.setup_instruction_memory_from(&ast) {
+            self.exit_with_diagnostic(&error);
+        }
+        let written = match format {
+            ImageFormat::SvRom | ImageFormat::VhdlRom => {
+                let rom = self.generate_rom(&ast, &module_name_from(file_path), format);
+                std::fs::write(output_path, rom).map_err(|_| format!("The file {} could not be written", output_path))
+            }
+            ImageFormat::Bin | ImageFormat::Hex => write_image(output_path, &self.instruction_memory, format),
+        };
+        if let Err(e) = written {
+            self.exit_with_error(&e);
+        }
+        self.print_info(&format!("Wrote {} words to {}", self.instruction_memory.len(), output_path));
+    }
+
+    /*
+        Assembles text as if it were the file called name, without
+        touching the disk for it. Includes go through source_provider.
+        Nothing is printed and no map is written: the notes come back in
+        the Program, or with the error that stopped the assembly. What a
+        previous program left behind is cleared first, so the same
+        Moonlight can assemble many programs.
+     */
+    pub fn assemble_source(&mut self, name: &str, text: &str, source_provider: &dyn SourceProvider) -> Result<Program, Vec<Diagnostic>> {
+        self.file_table.clear();
+        self.symbol_table.clear();
+        self.instruction_memory.clear();
+        self.sections.clear();
+        self.static_data_end = 0;
+        self.data_memory = vec![0; self.machine_profile.data_memory_size];
+        self.fill_pattern.fill(&mut self.data_memory);
+
+        let mut in_memory_provider = InMemorySourceProvider::over(source_provider);
+        in_memory_provider.insert(name, text);
+
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        let result = self.check_sources(name, &in_memory_provider, &mut diagnostics)
+            .and_then(|ast| self.setup_instruction_memory_from(&ast));
+        match result {
+            Ok(()) => Ok(Program {
+                instruction_memory: self.instruction_memory.clone(),
+                static_data: self.data_memory[..self.static_data_end].to_vec(),
+                symbol_table: self.symbol_table.clone(),
+                file_table: self.file_table.clone(),
+                diagnostics,
+            }),
+            Err(error) => {
+                diagnostics.push(error);
+                Err(diagnostics)
+            }
+        }
+    }
+
     pub fn run(&mut self, file_path: &str) {
-        let tokens: Vec<PositionedToken> = self.scan(file_path);
-        let ast = self.parse(&tokens);
-        self.load_symbol_table_from(&ast);
-        self.load_data_memory_from(&ast);
-        //self.setup_instruction_memory_from(&ast);
+        let _ast = self.check(file_path);
+        //self.setup_instruction_memory_from(&_ast);
+
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_come_back_with_the_diagnostics() {
+        let mut ml = Moonlight::new();
+        let diagnostics = ml.assemble_source("main.asm", ".inst\n\tadd &0\n", &InMemorySourceProvider::new()).unwrap_err();
+        let error = diagnostics.last().unwrap();
+        assert_eq!(error.severity, Severity::Error);
+        assert_eq!(error.position.map(|position| position.line), Some(2));
+    }
+
+    #[test]
+    fn notes_come_back_with_the_program_and_no_map_is_written() {
+        let map_path = std::env::temp_dir().join("moonlight_assemble_source_test.map");
+        let _ = std::fs::remove_file(&map_path);
+
+        let mut ml = Moonlight::new();
+        ml.map_path = Some(map_path.to_string_lossy().to_string());
+        ml.optimize_instructions = true;
+        let program = ml.assemble_source("main.asm", ".inst\n\tpush &0\n\tpop &0\n\tja $15\n", &InMemorySourceProvider::new()).unwrap();
+        assert_eq!(program.instruction_memory.len(), 1);
+        assert!(program.diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Note));
+        assert!(!map_path.exists());
+    }
 
+    #[test]
+    fn includes_go_through_the_given_provider() {
+        let mut source_provider = InMemorySourceProvider::new();
+        source_provider.insert("lib.asm", "\tja $15\n");
+        let mut ml = Moonlight::new();
+        let program = ml.assemble_source("main.asm", ".inst\n\taddi &0, 1\n.include \"lib.asm\"\n", &source_provider).unwrap();
+        assert_eq!(program.instruction_memory.len(), 2);
+        assert_eq!(program.file_table.get(&1).map(|name| name.as_str()), Some("lib.asm"));
     }
 }
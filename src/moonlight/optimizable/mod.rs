@@ -0,0 +1,3 @@
+pub mod optimizable;
+
+pub use optimizable::*;
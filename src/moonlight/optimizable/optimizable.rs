@@ -0,0 +1,189 @@
+use crate::moonlight::parseable::*;
+use crate::moonlight::utils::*;
+use crate::moonlight::Moonlight;
+
+fn is_zero(ptk: &PositionedToken) -> bool {
+    match ptk.token {
+        Token::Number(ref number) => number.to_i16() == Ok(0),
+        _ => false,
+    }
+}
+
+fn labels_the(label: &PositionedToken, instr_camp: Option<&InstrCamp>) -> bool {
+    let name = match label.token {
+        Token::LabelReference(ref name) => name,
+        _ => return false,
+    };
+    instr_camp.is_some_and(|instr_camp| {
+        instr_camp.label_declarations
+            .iter()
+            .any(|declaration| matches!(declaration.token, Token::LabelDeclaration(ref declared) if declared == name))
+    })
+}
+
+/*
+    How many instructions starting at index can be removed, or zero.
+    The rules, all of which leave registers, accumulators and the
+    stack pointer as they were:
+        - push &x followed by pop &x;
+        - mtac &x, $r followed by mfac &x, $r, or mfac followed by mtac,
+          drop the second move, which copies the value straight back;
+        - addi &x, 0 and subi &x, 0;
+        - jump _label where _label is the next instruction.
+ */
+fn removable_at(instr_field: &[InstrCamp], index: usize) -> (usize, usize) {
+    // (first instruction to remove, how many)
+    let current = &instr_field[index];
+    let next = instr_field.get(index + 1);
+
+    match (&current.instruction.token, &current.arg) {
+        (Token::Instruction(Instruction::Addi), InstrArg::AcNumber { number, .. })
+        | (Token::Instruction(Instruction::Subi), InstrArg::AcNumber { number, .. }) if is_zero(number) => (index, 1),
+
+        (Token::PseudoInstruction(PseudoInstruction::Jump), InstrArg::Jump { target }) if labels_the(target, next) => (index, 1),
+
+        _ => {
+            // The pairs can only go when nothing jumps in between them
+            let next = match next {
+                Some(next) if next.label_declarations.is_empty() => next,
+                _ => return (index, 0),
+            };
+            match (&current.instruction.token, &current.arg, &next.instruction.token, &next.arg) {
+                (
+                    Token::Instruction(Instruction::Push), InstrArg::Ac { ac: pushed },
+                    Token::Instruction(Instruction::Pop), InstrArg::Ac { ac: popped },
+                ) if pushed.token == popped.token => (index, 2),
+
+                (
+                    Token::Instruction(Instruction::Mtac), InstrArg::AcR { ac: first_ac, r: first_r },
+                    Token::Instruction(Instruction::Mfac), InstrArg::AcR { ac: second_ac, r: second_r },
+                )
+                | (
+                    Token::Instruction(Instruction::Mfac), InstrArg::AcR { ac: first_ac, r: first_r },
+                    Token::Instruction(Instruction::Mtac), InstrArg::AcR { ac: second_ac, r: second_r },
+                ) if first_ac.token == second_ac.token && first_r.token == second_r.token => (index + 1, 1),
+
+                _ => (index, 0),
+            }
+        }
+    }
+}
+
+pub trait Optimizable {
+    fn optimize(&self, ast: &mut Ast) -> Option<usize>;
+}
+
+impl Optimizable for Moonlight {
+    /*
+        Applies the peephole rules until none matches and returns how
+        many instructions were removed. Labels on a removed instruction
+        move to the one that follows it, so jumps to labels still land
+        on the same code. An instruction that is the last one and
+        carries labels is kept. The remaining instructions keep their
+        positions, so listings still point back to the source.
+
+        Nothing defines what a numeric jr, jrl, b*z or jump target
+        counts from, so removing code could move where it lands. When
+        any instruction has one, the instruction field is left as it is
+        and None is returned.
+
+        Pseudo instructions are not lowered anywhere yet, so the pass
+        works on the instruction field as parsed.
+     */
+    fn optimize(&self, ast: &mut Ast) -> Option<usize> {
+        if ast.instr_field.iter().any(|instr_camp| instr_camp.numeric_target().is_some()) {
+            return None;
+        }
+
+        let mut removed = 0;
+        let mut index = 0;
+        while index < ast.instr_field.len() {
+            let (start, count) = removable_at(&ast.instr_field, index);
+            let end = start + count;
+            let moved_labels: Vec<PositionedToken> = ast.instr_field[start..end]
+                .iter()
+                .flat_map(|instr_camp| instr_camp.label_declarations.iter().cloned())
+                .collect();
+
+            if count == 0 || (end >= ast.instr_field.len() && !moved_labels.is_empty()) {
+                index += 1;
+                continue;
+            }
+
+            ast.instr_field.drain(start..end);
+            if let Some(following) = ast.instr_field.get_mut(start) {
+                let mut label_declarations = moved_labels;
+                label_declarations.append(&mut following.label_declarations);
+                following.label_declarations = label_declarations;
+            }
+            removed += count;
+
+            // A removal can make the previous instruction match, like nested push/pop pairs
+            index = index.saturating_sub(1);
+        }
+        Some(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moonlight::scanneable::*;
+
+    fn optimized(source: &str) -> (Option<usize>, Ast) {
+        let mut ml = Moonlight::new();
+        let mut source_provider = InMemorySourceProvider::new();
+        source_provider.insert("test.asm", source);
+        let tokens = ml.scan("test.asm", &source_provider).unwrap();
+        let mut ast = ml.parse(&tokens).unwrap();
+        let removed = ml.optimize(&mut ast);
+        (removed, ast)
+    }
+
+    fn mnemonics(ast: &Ast) -> Vec<&'static str> {
+        ast.instr_field
+            .iter()
+            .map(|instr_camp| match instr_camp.instruction.token {
+                Token::Instruction(ref instruction) => instruction.mnemonic(),
+                Token::PseudoInstruction(ref pseudo_instruction) => pseudo_instruction.mnemonic(),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn numeric_branch_targets_leave_the_program_alone() {
+        let (removed, ast) = optimized(".inst\n_loop:\n\tsubi &0, 1\n\taddi &1, 0\n\tbnez &0, -2\n");
+        assert_eq!(removed, None);
+        assert_eq!(mnemonics(&ast), ["subi", "addi", "bnez"]);
+    }
+
+    #[test]
+    fn numeric_jump_targets_leave_the_program_alone() {
+        let (removed, ast) = optimized(".inst\n\tpush &0\n\tpop &0\n\tjump 0\n");
+        assert_eq!(removed, None);
+        assert_eq!(ast.instr_field.len(), 3);
+    }
+
+    #[test]
+    fn labels_move_to_the_following_instruction() {
+        let (removed, ast) = optimized(".inst\n\tjump _next\n_next:\n\taddi &0, 0\n\tsubi &1, 1\n\tjump _next\n");
+        assert_eq!(removed, Some(2));
+        assert_eq!(mnemonics(&ast), ["subi", "jump"]);
+        assert!(matches!(ast.instr_field[0].label_declarations[0].token, Token::LabelDeclaration(ref label) if label == "_next"));
+    }
+
+    #[test]
+    fn nested_pairs_collapse() {
+        let (removed, ast) = optimized(".inst\n\tpush &0\n\tpush &1\n\tpop &1\n\tpop &0\n\tmtac &2, $3\n\tmfac &2, $3\n\tret\n");
+        assert_eq!(removed, Some(5));
+        assert_eq!(mnemonics(&ast), ["mtac", "ret"]);
+    }
+
+    #[test]
+    fn a_labelled_second_instruction_keeps_the_pair() {
+        let (removed, ast) = optimized(".inst\n\tpush &0\n_back:\n\tpop &0\n\tjump _back\n");
+        assert_eq!(removed, Some(0));
+        assert_eq!(ast.instr_field.len(), 3);
+    }
+}
@@ -23,14 +23,31 @@ pub struct DataCamp {
     pub label_declarations: Vec<PositionedToken>,
     pub directive: PositionedToken,
     pub arg: DataArg,
+    pub section: String,
+    pub address: Option<usize>, // Set by the section layout on the first camp of a section placed at a fixed address
 }
 
 impl DataCamp {
-    pub fn new(label_declarations: Vec<PositionedToken>, directive: PositionedToken, arg: DataArg) -> Self {
+    pub fn new(label_declarations: Vec<PositionedToken>, directive: PositionedToken, arg: DataArg, section: String) -> Self {
         DataCamp {
             label_declarations,
             directive,
             arg,
+            section,
+            address: None,
+        }
+    }
+
+    // Bytes laid out by the directive, the same steps load_symbol_table_from takes
+    pub fn size(&self) -> usize {
+        match (&self.directive.token, &self.arg) {
+            (Token::Directive(Directive::Byte), DataArg::Values(values)) => values.len(),
+            (Token::Directive(Directive::Word), DataArg::Values(values)) => values.len() * 2,
+            (Token::Directive(Directive::Space), DataArg::Number(ptk)) => match ptk.token {
+                Token::Number(ref number) => number.to_u16().map(|n| n as usize).unwrap_or(0),
+                _ => 0,
+            },
+            _ => 0,
         }
     }
 }
@@ -154,6 +171,27 @@ impl InstrCamp {
             arg,
         }
     }
+
+    /*
+        A branch or jump written with a number instead of a label. Its
+        base is not defined yet, so callers treat the target as unknown.
+     */
+    pub fn numeric_target(&self) -> Option<&PositionedToken> {
+        let target = match (&self.instruction.token, &self.arg) {
+            (Token::Instruction(Instruction::Bgtz), InstrArg::AcNumber { number, .. })
+            | (Token::Instruction(Instruction::Bltz), InstrArg::AcNumber { number, .. })
+            | (Token::Instruction(Instruction::Beqz), InstrArg::AcNumber { number, .. })
+            | (Token::Instruction(Instruction::Bnez), InstrArg::AcNumber { number, .. })
+            | (Token::Instruction(Instruction::Jr), InstrArg::Number { number })
+            | (Token::Instruction(Instruction::Jrl), InstrArg::Number { number })
+            | (Token::PseudoInstruction(PseudoInstruction::Jump), InstrArg::Jump { target: number }) => number,
+            _ => return None,
+        };
+        match target.token {
+            Token::Number(_) => Some(target),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1,31 +1,31 @@
-use crate::moonlight::{debugable::Debugable, utils::*, Moonlight};
+use crate::moonlight::{debugable::Diagnostic, utils::*, Moonlight};
 use super::ast::*;
 
 enum Field {
-    Data,
+    Data(String), // Name of the current data section
     Inst,
 }
 pub trait Parseable {
-    fn parse(&mut self, tokens: &Vec<PositionedToken>) -> Ast;
+    fn parse(&mut self, tokens: &Vec<PositionedToken>) -> Result<Ast, Diagnostic>;
 
-    fn read_comma_separated_tokens(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Vec<PositionedToken>;
+    fn read_comma_separated_tokens(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<Vec<PositionedToken>, Diagnostic>;
     
-    fn read_jump_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg;
-    fn read_mul_div_swap_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg;
-    fn read_lw_sw_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg;
-    fn read_call_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg;
-
-    fn read_ac_r_r_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg;
-    fn read_ac_r_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg;
-    fn read_r_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg;
-    fn read_ac_r_number_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg;
-    fn read_ac_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg;
-    fn read_ac_number_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg;
-    fn read_number_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg;
+    fn read_jump_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic>;
+    fn read_mul_div_swap_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic>;
+    fn read_lw_sw_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic>;
+    fn read_call_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic>;
+
+    fn read_ac_r_r_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic>;
+    fn read_ac_r_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic>;
+    fn read_r_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic>;
+    fn read_ac_r_number_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic>;
+    fn read_ac_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic>;
+    fn read_ac_number_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic>;
+    fn read_number_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic>;
 }
 
 impl Parseable for Moonlight {
-    fn parse(&mut self, tokens: &Vec<PositionedToken>) -> Ast{
+    fn parse(&mut self, tokens: &Vec<PositionedToken>) -> Result<Ast, Diagnostic> {
         let mut label_declarations_accumulator: Vec<PositionedToken> = Vec::new();
         let mut data_field: Vec<DataCamp> = Vec::new();
         let mut instr_field: Vec<InstrCamp> = Vec::new();
@@ -40,10 +40,38 @@ impl Parseable for Moonlight {
             
             match ptk.token {
                 Token::Directive(Directive::Data) => {
-                    current_field = Field::Data;
+                    current_field = Field::Data(DATA_SECTION.to_string());
                     ptk_index += 1;
                     continue;
                 }
+                Token::Directive(Directive::Rodata) => {
+                    current_field = Field::Data(RODATA_SECTION.to_string());
+                    ptk_index += 1;
+                    continue;
+                }
+                Token::Directive(Directive::Bss) => {
+                    current_field = Field::Data(BSS_SECTION.to_string());
+                    ptk_index += 1;
+                    continue;
+                }
+                Token::Directive(Directive::Section) => {
+                    match tokens.get(ptk_index + 1).map(|next_ptk| (next_ptk.token.clone(), next_ptk.position)) {
+                        Some((Token::StringLiteral(name), position)) => {
+                            let is_identifier = name.starts_with(|c: char| c.is_ascii_alphabetic())
+                                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                            if !is_identifier {
+                                return Err(Diagnostic::positional_error("Section names must be made of letters, digits and underscores", position));
+                            } else if name == "inst" {
+                                return Err(Diagnostic::positional_error("The instruction field is selected with .inst, not with .section", position));
+                            }
+                            current_field = Field::Data(name);
+                        }
+                        Some((_, position)) => return Err(Diagnostic::positional_error("Expected a string literal after .section directive", position)),
+                        None => return Err(Diagnostic::positional_error("Expected a string literal after .section directive", ptk.position)),
+                    }
+                    ptk_index += 2;
+                    continue;
+                }
                 Token::Directive(Directive::Inst) => {
                     current_field = Field::Inst;
                     ptk_index += 1;
@@ -51,22 +79,29 @@ impl Parseable for Moonlight {
                 }
                 _ => {
                     match current_field {
-                        Field::Data => {
+                        Field::Data(ref section) => {
                             match ptk.token {
                                 Token::LabelDeclaration(_) => {
                                     label_declarations_accumulator.push(ptk.clone());
                                     ptk_index += 1;
                                     continue;
                                 }
+                                Token::Directive(Directive::Word) | Token::Directive(Directive::Byte) if section == BSS_SECTION => {
+                                    return Err(Diagnostic::positional_error("The .bss section is zero-initialised, only .space is allowed in it", ptk.position));
+                                }
                                 Token::Directive(Directive::Word) | Token::Directive(Directive::Byte) => {
-                                    let data = self.read_comma_separated_tokens(tokens, ptk_index + 1);
-                                    
+                                    let data = self.read_comma_separated_tokens(tokens, ptk_index + 1)?;
+                                    if data.is_empty() {
+                                        return Err(Diagnostic::positional_error("Expected at least one number after the directive", ptk.position));
+                                    }
+
                                     let data_len = data.len();
                                     data_field.push(
                                         DataCamp::new(
                                             label_declarations_accumulator.clone(),
                                             ptk.clone(),
                                             DataArg::new_values(data),
+                                            section.clone(),
                                         )
                                     );
                                     ptk_index += data_len * 2 ;
@@ -84,19 +119,20 @@ impl Parseable for Moonlight {
                                                             label_declarations_accumulator.clone(),
                                                             ptk.clone(),
                                                             DataArg::new_number(next_ptk.clone()),
+                                                            section.clone(),
                                                         )
                                                     );
                                                     ptk_index += 2;
                                                     label_declarations_accumulator.clear();
                                                     continue;
                                                 }
-                                                _ => self.exit_with_positional_error("Expect a number after space directive", next_ptk.position),
+                                                _ => return Err(Diagnostic::positional_error("Expect a number after space directive", next_ptk.position)),
                                             }
                                         }
-                                        None => self.exit_with_positional_error("Expect a number after space directive", ptk.position),
+                                        None => return Err(Diagnostic::positional_error("Expect a number after space directive", ptk.position)),
                                     }
                                 }
-                                _ => self.exit_with_positional_error("Expected a label declaration or directive in data field", ptk.position),
+                                _ => return Err(Diagnostic::positional_error("Expected a label declaration or directive in data field", ptk.position)),
                                     
                             }
                             
@@ -111,7 +147,7 @@ impl Parseable for Moonlight {
                                 Token::PseudoInstruction(ref psinstr) => {
                                     match psinstr {
                                         PseudoInstruction::Jump => {
-                                            let jump_arg = self.read_jump_format(&tokens, ptk_index + 1);
+                                            let jump_arg = self.read_jump_format(&tokens, ptk_index + 1)?;
                                             instr_field.push(
                                                 InstrCamp::new(
                                                     label_declarations_accumulator.clone(),
@@ -125,7 +161,7 @@ impl Parseable for Moonlight {
                                             continue;
                                         }
                                         PseudoInstruction::Lw | PseudoInstruction::Sw => {
-                                            let lw_sw_arg = self.read_lw_sw_format(&tokens, ptk_index + 1);
+                                            let lw_sw_arg = self.read_lw_sw_format(&tokens, ptk_index + 1)?;
                                             instr_field.push(
                                                 InstrCamp::new(
                                                     label_declarations_accumulator.clone(),
@@ -139,7 +175,7 @@ impl Parseable for Moonlight {
                                             continue;
                                         }
                                         PseudoInstruction::Mul | PseudoInstruction::Div | PseudoInstruction::Swap => {
-                                            let mul_div_swap_arg = self.read_mul_div_swap_format(&tokens, ptk_index + 1);
+                                            let mul_div_swap_arg = self.read_mul_div_swap_format(&tokens, ptk_index + 1)?;
                                             instr_field.push(
                                                 InstrCamp::new(
                                                     label_declarations_accumulator.clone(),
@@ -153,7 +189,7 @@ impl Parseable for Moonlight {
                                             continue;
                                         }
                                         PseudoInstruction::Call => {
-                                            let call_arg = self.read_call_format(&tokens, ptk_index + 1);
+                                            let call_arg = self.read_call_format(&tokens, ptk_index + 1)?;
                                             instr_field.push(
                                                 InstrCamp::new(
                                                     label_declarations_accumulator.clone(),
@@ -182,172 +218,60 @@ impl Parseable for Moonlight {
                                     }
                                 }
                                 Token::Instruction(ref instr) => {
-                                    match instr {
-                                        Instruction::Nope => {
-                                            instr_field.push(
-                                                InstrCamp::new(
-                                                    label_declarations_accumulator.clone(),
-                                                    ptk.clone(),
-                                                    InstrArg::new_empty(),
-                                                ),
-                                            );
-                                            ptk_index += 1;
-                                            label_declarations_accumulator.clear();
-                                            continue;
-                                        }
-                                        Instruction::Add
-                                        | Instruction::Sub
-                                        | Instruction::And
-                                        | Instruction::Or
-                                        | Instruction::Xor
-                                        | Instruction::Nand
-                                        | Instruction::Nor
-                                        | Instruction::Xnor
-                                        | Instruction::Slt 
-                                        | Instruction::Lwr
-                                        | Instruction::Swr
-                                        => {
-                                            // AC_R_R
-                                            let ac_r_r_arg = self.read_ac_r_r_format(&tokens, ptk_index + 1);
-                                            instr_field.push(
-                                                InstrCamp::new(
-                                                    label_declarations_accumulator.clone(),
-                                                    ptk.clone(),
-                                                    ac_r_r_arg,
-                                                ),
-                                            );
-                                            ptk_index += 6;
-                                            label_declarations_accumulator.clear();
-                                            continue;
-                                        }
-                                        Instruction::Not
-                                        | Instruction::Mtac
-                                        | Instruction::Mfac
-
-                                        | Instruction::Bgtzr
-                                        | Instruction::Bltzr
-                                        | Instruction::Beqzr
-                                        | Instruction::Bnezr
-                                        => {
-                                            // AC_R
-                                            let ac_r_arg = self.read_ac_r_format(&tokens, ptk_index + 1);
-                                            instr_field.push(
-                                                InstrCamp::new(
-                                                    label_declarations_accumulator.clone(),
-                                                    ptk.clone(),
-                                                    ac_r_arg,
-                                                ),
-                                            );
-                                            ptk_index += 4;
-                                            label_declarations_accumulator.clear();
-                                            continue;
-                                        }
-                                        Instruction::Tmul
-                                        | Instruction::Tdiv
-                                        | Instruction::Ja
-                                        | Instruction::Jal
-                                        => {
-                                            // R
-                                            let r_arg = self.read_r_format(&tokens, ptk_index + 1);
-                                            instr_field.push(
-                                                InstrCamp::new(
-                                                    label_declarations_accumulator.clone(),
-                                                    ptk.clone(),
-                                                    r_arg,
-                                                ),
-                                            );
-                                            ptk_index += 2;
-                                            label_declarations_accumulator.clear();
-                                            continue;
-                                        }
-                                        Instruction::Sll
-                                        | Instruction::Srl
-                                        | Instruction::Sra
-                                        => {
-                                            // AC_R_NUMBER
-                                            let ac_r_number_arg = self.read_ac_r_number_format(&tokens, ptk_index + 1);
-                                            instr_field.push(
-                                                InstrCamp::new(
-                                                    label_declarations_accumulator.clone(),
-                                                    ptk.clone(),
-                                                    ac_r_number_arg,
-                                                ),
-                                            );
-                                            ptk_index += 6;
-                                            label_declarations_accumulator.clear();
-                                            continue;
-                                        }
-                                        Instruction::Mtl
-                                        | Instruction::Mfl
-                                        | Instruction::Mth
-                                        | Instruction::Mfh
-                                        | Instruction::Push
-                                        | Instruction::Pop
-                                        => {
-                                            // AC
-                                            let ac_arg = self.read_ac_format(&tokens, ptk_index + 1);
-                                            instr_field.push(
-                                                InstrCamp::new(
-                                                    label_declarations_accumulator.clone(),
-                                                    ptk.clone(),
-                                                    ac_arg,
-                                                ),
-                                            );
-                                            ptk_index += 2;
-                                            label_declarations_accumulator.clear();
-                                            continue;
-                                        }
-                                        Instruction::Addi  
-                                        | Instruction::Subi
-                                        | Instruction::Andi
-                                        | Instruction::Ori
-                                        | Instruction::Xori
-                                        | Instruction::Nandi
-                                        | Instruction::Nori
-                                        | Instruction::Xnori
-                                        | Instruction::Lli
-                                        | Instruction::Lui
-                                        | Instruction::Lsi
-
-                                        | Instruction::Bgtz
-                                        | Instruction::Bltz
-                                        | Instruction::Beqz
-                                        | Instruction::Bnez
-                                        => {
-                                            // AC_NUMBER
-                                            let ac_number_arg = self.read_ac_number_format(&tokens, ptk_index + 1);
-                                            instr_field.push(
-                                                InstrCamp::new(
-                                                    label_declarations_accumulator.clone(),
-                                                    ptk.clone(),
-                                                    ac_number_arg,
-                                                ),
-                                            );
-                                            ptk_index += 4;
-                                            label_declarations_accumulator.clear();
-                                            continue;
-                                        }
-                                        Instruction::Jr
-                                        | Instruction::Jrl
-                                        => {
-                                            // NUMBER
-                                            let number_arg = self.read_number_format(&tokens, ptk_index + 1);
-                                            instr_field.push(
-                                                InstrCamp::new(
-                                                    label_declarations_accumulator.clone(),
-                                                    ptk.clone(),
-                                                    number_arg,
-                                                ),
-                                            );
-                                            ptk_index += 2;
-                                            label_declarations_accumulator.clear();
-                                            continue;
-                                        }
-                                        
+                                    // The operand format of each instruction comes from the ISA table
+                                    let format = instr.format();
+                                    let instr_arg = match format {
+                                        InstructionFormat::Empty => InstrArg::new_empty(),
+                                        InstructionFormat::AcRR => self.read_ac_r_r_format(tokens, ptk_index + 1)?,
+                                        InstructionFormat::AcR => self.read_ac_r_format(tokens, ptk_index + 1)?,
+                                        InstructionFormat::R => self.read_r_format(tokens, ptk_index + 1)?,
+                                        InstructionFormat::AcRNumber => self.read_ac_r_number_format(tokens, ptk_index + 1)?,
+                                        InstructionFormat::Ac => self.read_ac_format(tokens, ptk_index + 1)?,
+                                        InstructionFormat::AcNumber => self.read_ac_number_format(tokens, ptk_index + 1)?,
+                                        InstructionFormat::Number => self.read_number_format(tokens, ptk_index + 1)?,
+                                    };
+                                    instr_field.push(
+                                        InstrCamp::new(
+                                            label_declarations_accumulator.clone(),
+                                            ptk.clone(),
+                                            instr_arg,
+                                        ),
+                                    );
+                                    ptk_index += 1 + format.token_count();
+                                    label_declarations_accumulator.clear();
+                                    continue;
+                                }
+                                Token::Directive(Directive::Raw) => {
+                                    // Raw instruction words, one InstrCamp each, as written by the disassembler
+                                    let words = self.read_comma_separated_tokens(tokens, ptk_index + 1)?;
+                                    if words.is_empty() {
+                                        return Err(Diagnostic::positional_error("Expected at least one number after .raw directive", ptk.position));
+                                    }
+
+                                    let words_len = words.len();
+                                    for word in words {
+                                        instr_field.push(
+                                            InstrCamp::new(
+                                                label_declarations_accumulator.clone(),
+                                                ptk.clone(),
+                                                InstrArg::new_number(word),
+                                            ),
+                                        );
+                                        label_declarations_accumulator.clear();
                                     }
+                                    ptk_index += words_len * 2;
+                                    continue;
+                                }
+                                Token::Directive(Directive::Include) => {
+                                    return Err(Diagnostic::positional_error("Expected a string literal after .include directive.", ptk.position));
+                                }
+                                Token::Directive(Directive::Word) | Token::Directive(Directive::Byte) | Token::Directive(Directive::Space) => {
+                                    return Err(Diagnostic::positional_error(
+                                        "Data declarations belong in a data field, write .raw for raw instruction words",
+                                        ptk.position,
+                                    ));
                                 }
-                                Token::Directive(Directive::Include) => unreachable!(),
-                                _ => self.exit_with_positional_error("Expected a label declaration, instruction or pseudo instruction", ptk.position),
+                                _ => return Err(Diagnostic::positional_error("Expected a label declaration, instruction, pseudo instruction or .raw", ptk.position)),
                             }
                         }
                     }
@@ -356,21 +280,21 @@ impl Parseable for Moonlight {
             //ptk_index += 1;
         }
 
-        Ast {
+        Ok(Ast {
             data_field,
             instr_field,
-        }
+        })
     }
 
-    fn read_comma_separated_tokens(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Vec<PositionedToken> {
+    fn read_comma_separated_tokens(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<Vec<PositionedToken>, Diagnostic> {
         let mut result = Vec::new();
         let mut current_index = start_index;
         
         while let Some(ptk) = tokens.get(current_index) {
             match ptk.token {
-                Token::Comma => return result,
+                Token::Comma => return Ok(result),
                 Token::Number(_) => result.push(ptk.clone()),
-                _ => self.exit_with_positional_error("Expect a comma separated number ", ptk.position),
+                _ => return Err(Diagnostic::positional_error("Expect a comma separated number ", ptk.position)),
             }
 
             match tokens.get(current_index + 1) {
@@ -379,43 +303,42 @@ impl Parseable for Moonlight {
                         Token::Comma => {
                             current_index += 2;
                         }
-                        _ => return result,
+                        _ => return Ok(result),
                     }
                 }
-                None => return result,
+                None => return Ok(result),
             }
         }
-        result
+        Ok(result)
     }
 
 
-    fn read_jump_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg {
+    fn read_jump_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic> {
         // example jump _label|Number
         match tokens.get(start_index) {
             Some(ptk) => {
                 match ptk.token {
                     Token::LabelReference(_) => {
-                        return InstrArg::new_jump(ptk.clone());
+                        Ok(InstrArg::new_jump(ptk.clone()))
                     }
                     Token::Number(_) => {
-                        return InstrArg::new_jump(ptk.clone());
+                        Ok(InstrArg::new_jump(ptk.clone()))
                     }
-                    _ => self.exit_with_positional_error("Expect a label reference or number after pseudo instruction format", ptk.position),
+                    _ => Err(Diagnostic::positional_error("Expect a label reference or number after pseudo instruction format", ptk.position)),
                 }
             }
             None => {
                 match tokens.get(start_index - 1) {
                     Some(bptk) => {
-                        self.exit_with_positional_error("Expect a label reference or number after pseudo instruction format", bptk.position);
+                        Err(Diagnostic::positional_error("Expect a label reference or number after pseudo instruction format", bptk.position))
                     }
                     None => unreachable!(),
                 }
             }
         }
-        unreachable!();
     }
 
-    fn read_mul_div_swap_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg {
+    fn read_mul_div_swap_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic> {
         // example mul $1, $2
         let mut tokens_to_process = Vec::new();
         for i in 0..=2 {
@@ -438,36 +361,35 @@ impl Parseable for Moonlight {
                                             Some(ptk2) => {
                                                 match ptk2.token {
                                                     Token::Register(_) => {
-                                                        return InstrArg::new_mul_div_swap(ptk0.clone(), ptk2.clone());
+                                                        Ok(InstrArg::new_mul_div_swap(ptk0.clone(), ptk2.clone()))
                                                     }
-                                                    _ => self.exit_with_positional_error("Expect a register reference after comma in this pseudo instruction format", ptk2.position),
+                                                    _ => Err(Diagnostic::positional_error("Expect a register reference after comma in this pseudo instruction format", ptk2.position)),
                                                 }
                                             }
-                                            None => self.exit_with_positional_error("Expect a register reference after comma in this pseudo instruction format", ptk1.position),
+                                            None => Err(Diagnostic::positional_error("Expect a register reference after comma in this pseudo instruction format", ptk1.position)),
                                         }
                                     }
-                                    _ => self.exit_with_positional_error("Expect a comma after register in this pseudo instruction format", ptk1.position),
+                                    _ => Err(Diagnostic::positional_error("Expect a comma after register in this pseudo instruction format", ptk1.position)),
                                 }
                             }
-                            None => self.exit_with_positional_error("Expect a comma after register in this pseudo instruction format", ptk0.position),
+                            None => Err(Diagnostic::positional_error("Expect a comma after register in this pseudo instruction format", ptk0.position)),
                         }
                     }
-                    _ => self.exit_with_positional_error("Expect a register in this pseudo instruction format", ptk0.position),
+                    _ => Err(Diagnostic::positional_error("Expect a register in this pseudo instruction format", ptk0.position)),
                 }
             }
             None => {
                 match tokens.get(start_index-1) {
                     Some(bptk) => {
-                        self.exit_with_positional_error("Expect an accumulator after pseudo instruction format", bptk.position);
+                        Err(Diagnostic::positional_error("Expect an accumulator after pseudo instruction format", bptk.position))
                     }
                     None => unreachable!(),
                 }
             }
         }
-        unreachable!();
     }
 
-    fn read_lw_sw_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg {
+    fn read_lw_sw_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic> {
         /*
             PositionedToken { token: Accumulator(Ac0), position: Position { file: 0, line: 4, column: Some(12) } }
             PositionedToken { token: Comma, position: Position { file: 0, line: 4, column: Some(12) } }
@@ -509,82 +431,80 @@ impl Parseable for Moonlight {
                                                                                             Some(ptk5) => {
                                                                                                 match ptk5.token {
                                                                                                     Token::RightSquareBracket => {
-                                                                                                        return InstrArg::new_lw_sw(
+                                                                                                        Ok(InstrArg::new_lw_sw(
                                                                                                             ptk0.clone(),
                                                                                                             ptk2.clone(),
                                                                                                             ptk4.clone(),
-                                                                                                        );
+                                                                                                        ))
                                                                                                     }
-                                                                                                    _ => self.exit_with_positional_error("Expect a right square bracket after number in memory pseudo instruction format", ptk5.position),
+                                                                                                    _ => Err(Diagnostic::positional_error("Expect a right square bracket after number in memory pseudo instruction format", ptk5.position)),
                                                                                                 }
                                                                                             }
-                                                                                            None => self.exit_with_positional_error("Expect a right square bracket after number in memory pseudo instruction format", ptk4.position),
+                                                                                            None => Err(Diagnostic::positional_error("Expect a right square bracket after number in memory pseudo instruction format", ptk4.position)),
                                                                                         }
                                                                                     }
-                                                                                    _ => self.exit_with_positional_error("Expect a number after left square bracket in memory pseudo instruction format", ptk4.position),
+                                                                                    _ => Err(Diagnostic::positional_error("Expect a number after left square bracket in memory pseudo instruction format", ptk4.position)),
                                                                                 }
                                                                             }
-                                                                            None => self.exit_with_positional_error("Expect a number after left square bracket in memory pseudo instruction format", ptk3.position),
+                                                                            None => Err(Diagnostic::positional_error("Expect a number after left square bracket in memory pseudo instruction format", ptk3.position)),
                                                                         }
                                                                     }
-                                                                    _ => self.exit_with_positional_error("Expect a left square bracket after label reference in memory pseudo instruction format", ptk3.position),
+                                                                    _ => Err(Diagnostic::positional_error("Expect a left square bracket after label reference in memory pseudo instruction format", ptk3.position)),
                                                                 }
                                                             }
-                                                            None => self.exit_with_positional_error("Expect a left square bracket after label reference in memory pseudo instruction format", ptk2.position),
+                                                            None => Err(Diagnostic::positional_error("Expect a left square bracket after label reference in memory pseudo instruction format", ptk2.position)),
                                                         }
                                                     }
-                                                    _ => self.exit_with_positional_error("Expect a label reference after comma in memory pseudo instruction format", ptk2.position),
+                                                    _ => Err(Diagnostic::positional_error("Expect a label reference after comma in memory pseudo instruction format", ptk2.position)),
                                                 }
                                             }
-                                            None => self.exit_with_positional_error("Expect a label reference after comma in memory pseudo instruction format", ptk1.position),
+                                            None => Err(Diagnostic::positional_error("Expect a label reference after comma in memory pseudo instruction format", ptk1.position)),
                                         }
                                     }
-                                    _ => self.exit_with_positional_error("Expect a comma after accumulator in memory pseudo instruction format", ptk1.position),
+                                    _ => Err(Diagnostic::positional_error("Expect a comma after accumulator in memory pseudo instruction format", ptk1.position)),
                                 }
                             }
-                            None => self.exit_with_positional_error("Expect a comma after accumulator in memory pseudo instruction format", ptk0.position),
+                            None => Err(Diagnostic::positional_error("Expect a comma after accumulator in memory pseudo instruction format", ptk0.position)),
                         }
                     }
-                    _ => self.exit_with_positional_error("Expect an accumulator for memory pseudo instruction format", ptk0.position),
+                    _ => Err(Diagnostic::positional_error("Expect an accumulator for memory pseudo instruction format", ptk0.position)),
                 }
             }
             None => {
                 match tokens.get(start_index-1) {
                     Some(bptk) => {
-                        self.exit_with_positional_error("Expect an accumulator after pseudo instruction", bptk.position);
+                        Err(Diagnostic::positional_error("Expect an accumulator after pseudo instruction", bptk.position))
                     }
                     None => unreachable!(),
                 }
             }
         }
-        unreachable!();
     }
 
-    fn read_call_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg {
+    fn read_call_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic> {
         // like jump, but only for label references
         match tokens.get(start_index) {
             Some(ptk) => {
                 match ptk.token {
                     Token::LabelReference(_) => {
-                        return InstrArg::new_call(ptk.clone());
+                        Ok(InstrArg::new_call(ptk.clone()))
                     }
-                    _ => self.exit_with_positional_error("Expect a label reference after call pseudo instruction format", ptk.position),
+                    _ => Err(Diagnostic::positional_error("Expect a label reference after call pseudo instruction format", ptk.position)),
                 }
             }
             None => {
                 match tokens.get(start_index - 1) {
                     Some(bptk) => {
-                        self.exit_with_positional_error("Expect a label reference after call pseudo instruction format", bptk.position);
+                        Err(Diagnostic::positional_error("Expect a label reference after call pseudo instruction format", bptk.position))
                     }
                     None => unreachable!(),
                 }
             }
         }
-        unreachable!();
     }
 
 
-    fn read_ac_r_r_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg {
+    fn read_ac_r_r_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic> {
         // example add &0, $1, $2
         let mut tokens_to_process = Vec::new();
         for i in 0..=4 {
@@ -615,48 +535,47 @@ impl Parseable for Moonlight {
                                                                             Some(ptk4) => {
                                                                                 match ptk4.token {
                                                                                     Token::Register(_) => {
-                                                                                        return InstrArg::new_ac_r_r(ptk0.clone(), ptk2.clone(), ptk4.clone());
+                                                                                        Ok(InstrArg::new_ac_r_r(ptk0.clone(), ptk2.clone(), ptk4.clone()))
                                                                                     }
-                                                                                    _ => self.exit_with_positional_error("Expect a register after comma in this instruction format", ptk4.position),
+                                                                                    _ => Err(Diagnostic::positional_error("Expect a register after comma in this instruction format", ptk4.position)),
                                                                                 }
                                                                             }
-                                                                            None => self.exit_with_positional_error("Expect a register after comma in this instruction format", ptk3.position),
+                                                                            None => Err(Diagnostic::positional_error("Expect a register after comma in this instruction format", ptk3.position)),
                                                                         }
                                                                     }
-                                                                    _ => self.exit_with_positional_error("Expect a comma after register in this instruction format", ptk3.position),
+                                                                    _ => Err(Diagnostic::positional_error("Expect a comma after register in this instruction format", ptk3.position)),
                                                                 }
                                                             }
-                                                            None => self.exit_with_positional_error("Expect a comma after register in this instruction format", ptk2.position),
+                                                            None => Err(Diagnostic::positional_error("Expect a comma after register in this instruction format", ptk2.position)),
                                                         }
                                                     }
-                                                    _ => self.exit_with_positional_error("Expect a register after comma in this instruction format", ptk2.position),
+                                                    _ => Err(Diagnostic::positional_error("Expect a register after comma in this instruction format", ptk2.position)),
                                                 }
                                             }
-                                            None => self.exit_with_positional_error("Expect a register after comma in this instruction format", ptk1.position),
+                                            None => Err(Diagnostic::positional_error("Expect a register after comma in this instruction format", ptk1.position)),
                                         }
                                     }
-                                    _ => self.exit_with_positional_error("Expect a comma after accumulator in this instruction format", ptk1.position),
+                                    _ => Err(Diagnostic::positional_error("Expect a comma after accumulator in this instruction format", ptk1.position)),
                                 }
                             }
-                            None => self.exit_with_positional_error("Expect a comma after accumulator in this instruction format", ptk0.position),
+                            None => Err(Diagnostic::positional_error("Expect a comma after accumulator in this instruction format", ptk0.position)),
                         }
                     }
-                    _ => self.exit_with_positional_error("Expect an accumulator in this instruction format", ptk0.position),
+                    _ => Err(Diagnostic::positional_error("Expect an accumulator in this instruction format", ptk0.position)),
                 }
             }
             None => {
                 match tokens.get(start_index-1) {
                     Some(bptk) => {
-                        self.exit_with_positional_error("Expect an accumulator in this instruction format", bptk.position);
+                        Err(Diagnostic::positional_error("Expect an accumulator in this instruction format", bptk.position))
                     }
                     None => unreachable!(),
                 }
             }
         }
-        unreachable!();
     }
 
-    fn read_ac_r_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg {
+    fn read_ac_r_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic> {
         // example not &0, $1
         let mut tokens_to_process = Vec::new();
         for i in 0..=2 {
@@ -679,59 +598,57 @@ impl Parseable for Moonlight {
                                             Some(ptk2) => {
                                                 match ptk2.token {
                                                     Token::Register(_) => {
-                                                        return InstrArg::new_ac_r(ptk0.clone(), ptk2.clone());
+                                                        Ok(InstrArg::new_ac_r(ptk0.clone(), ptk2.clone()))
                                                     }
-                                                    _ => self.exit_with_positional_error("Expect a register after comma in this instruction format", ptk2.position),
+                                                    _ => Err(Diagnostic::positional_error("Expect a register after comma in this instruction format", ptk2.position)),
                                                 }
                                             }
-                                            None => self.exit_with_positional_error("Expect a register after comma in this instruction format", ptk1.position),
+                                            None => Err(Diagnostic::positional_error("Expect a register after comma in this instruction format", ptk1.position)),
                                         }
                                     }
-                                    _ => self.exit_with_positional_error("Expect a comma after accumulator in this instruction format", ptk1.position),
+                                    _ => Err(Diagnostic::positional_error("Expect a comma after accumulator in this instruction format", ptk1.position)),
                                 }
                             }
-                            None => self.exit_with_positional_error("Expect a comma after accumulator in this instruction format", ptk0.position),
+                            None => Err(Diagnostic::positional_error("Expect a comma after accumulator in this instruction format", ptk0.position)),
                         }
                     }
-                    _ => self.exit_with_positional_error("Expect an accumulator in this instruction format", ptk0.position),
+                    _ => Err(Diagnostic::positional_error("Expect an accumulator in this instruction format", ptk0.position)),
                 }
             }
             None => {
                 match tokens.get(start_index-1) {
                     Some(bptk) => {
-                        self.exit_with_positional_error("Expect an accumulator in this instruction format", bptk.position);
+                        Err(Diagnostic::positional_error("Expect an accumulator in this instruction format", bptk.position))
                     }
                     None => unreachable!(),
                 }
             }
         }
-        unreachable!();
     }
 
-    fn read_r_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg {
+    fn read_r_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic> {
         // example inst $1
         match tokens.get(start_index) {
             Some(ptk) => {
                 match ptk.token {
                     Token::Register(_) => {
-                        return InstrArg::new_r(ptk.clone());
+                        Ok(InstrArg::new_r(ptk.clone()))
                     }
-                    _ => self.exit_with_positional_error("Expect a register in this instruction format", ptk.position),
+                    _ => Err(Diagnostic::positional_error("Expect a register in this instruction format", ptk.position)),
                 }
             }
             None => {
                 match tokens.get(start_index - 1) {
                     Some(bptk) => {
-                        self.exit_with_positional_error("Expect a register in this instruction format", bptk.position);
+                        Err(Diagnostic::positional_error("Expect a register in this instruction format", bptk.position))
                     }
                     None => unreachable!(),
                 }
             }
-        };
-        unreachable!();
+        }
     }
 
-    fn read_ac_r_number_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg {
+    fn read_ac_r_number_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic> {
         // example sll &0, $1, 5
         let mut tokens_to_process = Vec::new();
         for i in 0..=4 {
@@ -762,72 +679,70 @@ impl Parseable for Moonlight {
                                                                             Some(ptk4) => {
                                                                                 match ptk4.token {
                                                                                     Token::Number(_) => {
-                                                                                        return InstrArg::new_ac_r_number(ptk0.clone(), ptk2.clone(), ptk4.clone());
+                                                                                        Ok(InstrArg::new_ac_r_number(ptk0.clone(), ptk2.clone(), ptk4.clone()))
                                                                                     }
-                                                                                    _ => self.exit_with_positional_error("Expect a number after comma in this instruction format", ptk4.position),
+                                                                                    _ => Err(Diagnostic::positional_error("Expect a number after comma in this instruction format", ptk4.position)),
                                                                                 }
                                                                             }
-                                                                            None => self.exit_with_positional_error("Expect a number after comma in this instruction format", ptk3.position),
+                                                                            None => Err(Diagnostic::positional_error("Expect a number after comma in this instruction format", ptk3.position)),
                                                                         }
                                                                     }
-                                                                    _ => self.exit_with_positional_error("Expect a comma after register in this instruction format", ptk3.position),
+                                                                    _ => Err(Diagnostic::positional_error("Expect a comma after register in this instruction format", ptk3.position)),
                                                                 }
                                                             }
-                                                            None => self.exit_with_positional_error("Expect a comma after register in this instruction format", ptk2.position),
+                                                            None => Err(Diagnostic::positional_error("Expect a comma after register in this instruction format", ptk2.position)),
                                                         }
                                                     }
-                                                    _ => self.exit_with_positional_error("Expect a register after comma in this instruction format", ptk2.position),
+                                                    _ => Err(Diagnostic::positional_error("Expect a register after comma in this instruction format", ptk2.position)),
                                                 }
                                             }
-                                            None => self.exit_with_positional_error("Expect a register after comma in this instruction format", ptk1.position),
+                                            None => Err(Diagnostic::positional_error("Expect a register after comma in this instruction format", ptk1.position)),
                                         }
                                     }
-                                    _ => self.exit_with_positional_error("Expect a comma after accumulator in this instruction format", ptk1.position),
+                                    _ => Err(Diagnostic::positional_error("Expect a comma after accumulator in this instruction format", ptk1.position)),
                                 }
                             }
-                            None => self.exit_with_positional_error("Expect a comma after accumulator in this instruction format", ptk0.position),
+                            None => Err(Diagnostic::positional_error("Expect a comma after accumulator in this instruction format", ptk0.position)),
                         }
                     }
-                    _ => self.exit_with_positional_error("Expect an accumulator in this instruction format", ptk0.position),
+                    _ => Err(Diagnostic::positional_error("Expect an accumulator in this instruction format", ptk0.position)),
                 }
             }
             None => {
                 match tokens.get(start_index-1) {
                     Some(bptk) => {
-                        self.exit_with_positional_error("Expect an accumulator in this instruction format", bptk.position);
+                        Err(Diagnostic::positional_error("Expect an accumulator in this instruction format", bptk.position))
                     }
                     None => unreachable!(),
                 }
             }
-        };
-        unreachable!();
+        }
     }
 
 
-    fn read_ac_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg {
+    fn read_ac_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic> {
         // example mtac &0
         match tokens.get(start_index) {
             Some(ptk) => {
                 match ptk.token {
                     Token::Accumulator(_) => {
-                        return InstrArg::new_ac(ptk.clone());
+                        Ok(InstrArg::new_ac(ptk.clone()))
                     }
-                    _ => self.exit_with_positional_error("Expect an accumulator in this instruction format", ptk.position),
+                    _ => Err(Diagnostic::positional_error("Expect an accumulator in this instruction format", ptk.position)),
                 }
             }
             None => {
                 match tokens.get(start_index - 1) {
                     Some(bptk) => {
-                        self.exit_with_positional_error("Expect an accumulator in this instruction format", bptk.position);
+                        Err(Diagnostic::positional_error("Expect an accumulator in this instruction format", bptk.position))
                     }
                     None => unreachable!(),
                 }
             }
-        };
-        unreachable!();
+        }
     }
 
-    fn read_ac_number_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg {
+    fn read_ac_number_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic> {
         // example addi &0, 5
         let mut tokens_to_process = Vec::new();
         for i in 0..=2 {
@@ -850,56 +765,54 @@ impl Parseable for Moonlight {
                                             Some(ptk2) => {
                                                 match ptk2.token {
                                                     Token::Number(_) => {
-                                                        return InstrArg::new_ac_number(ptk0.clone(), ptk2.clone());
+                                                        Ok(InstrArg::new_ac_number(ptk0.clone(), ptk2.clone()))
                                                     }
-                                                    _ => self.exit_with_positional_error("Expect a number after comma in this instruction format", ptk2.position),
+                                                    _ => Err(Diagnostic::positional_error("Expect a number after comma in this instruction format", ptk2.position)),
                                                 }
                                             }
-                                            None => self.exit_with_positional_error("Expect a number after comma in this instruction format", ptk1.position),
+                                            None => Err(Diagnostic::positional_error("Expect a number after comma in this instruction format", ptk1.position)),
                                         }
                                     }
-                                    _ => self.exit_with_positional_error("Expect a comma after accumulator in this instruction format", ptk1.position),
+                                    _ => Err(Diagnostic::positional_error("Expect a comma after accumulator in this instruction format", ptk1.position)),
                                 }
                             }
-                            None => self.exit_with_positional_error("Expect a comma after accumulator in this instruction format", ptk0.position),
+                            None => Err(Diagnostic::positional_error("Expect a comma after accumulator in this instruction format", ptk0.position)),
                         }
                     }
-                    _ => self.exit_with_positional_error("Expect an accumulator in this instruction format", ptk0.position),
+                    _ => Err(Diagnostic::positional_error("Expect an accumulator in this instruction format", ptk0.position)),
                 }
             }
             None => {
                 match tokens.get(start_index-1) {
                     Some(bptk) => {
-                        self.exit_with_positional_error("Expect an accumulator in this instruction format", bptk.position);
+                        Err(Diagnostic::positional_error("Expect an accumulator in this instruction format", bptk.position))
                     }
                     None => unreachable!(),
                 }
             }
-        };
-        unreachable!();
+        }
     }
 
-    fn read_number_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> InstrArg {
+    fn read_number_format(&self, tokens: &Vec<PositionedToken>, start_index: usize) -> Result<InstrArg, Diagnostic> {
         // example lli 5
         match tokens.get(start_index) {
             Some(ptk) => {
                 match ptk.token {
                     Token::Number(_) => {
-                        return InstrArg::new_number(ptk.clone());
+                        Ok(InstrArg::new_number(ptk.clone()))
                     }
-                    _ => self.exit_with_positional_error("Expect a number in this instruction format", ptk.position),
+                    _ => Err(Diagnostic::positional_error("Expect a number in this instruction format", ptk.position)),
                 }
             }
             None => {
                 match tokens.get(start_index - 1) {
                     Some(bptk) => {
-                        self.exit_with_positional_error("Expect a number in this instruction format", bptk.position);
+                        Err(Diagnostic::positional_error("Expect a number in this instruction format", bptk.position))
                     }
                     None => unreachable!(),
                 }
             }
-        };
-        unreachable!();
+        }
     }
 
 }
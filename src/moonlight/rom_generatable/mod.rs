@@ -0,0 +1,3 @@
+pub mod rom_generatable;
+
+pub use rom_generatable::*;
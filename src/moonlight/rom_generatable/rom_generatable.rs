@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use crate::moonlight::disassemblable::*;
+use crate::moonlight::instruction_memory_loadable::*;
+use crate::moonlight::parseable::*;
+use crate::moonlight::Moonlight;
+
+/*
+    Turns a file path into an HDL identifier: the file stem with every
+    character that is not a letter, digit or underscore replaced.
+ */
+pub fn module_name_from(file_path: &str) -> String {
+    let stem = std::path::Path::new(file_path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut name: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert_str(0, "m_");
+    }
+    name
+}
+
+fn address_width(depth: usize) -> usize {
+    let mut width = 1;
+    while (1usize << width) < depth {
+        width += 1;
+    }
+    width
+}
+
+struct RomWord {
+    word: u16,
+    comment: String,
+}
+
+struct RamByte {
+    address: usize,
+    byte: u8,
+    comment: Option<String>,
+}
+
+pub trait RomGeneratable {
+    fn generate_rom(&self, ast: &Ast, module_name: &str, format: ImageFormat) -> String;
+}
+
+impl Moonlight {
+    fn rom_words(&self, ast: &Ast) -> Vec<RomWord> {
+        self.instruction_memory
+            .iter()
+            .zip(ast.instr_field.iter())
+            .map(|(word, instr_camp)| {
+                let position = instr_camp.instruction.position;
+                RomWord {
+                    word: *word,
+                    comment: format!(
+                        "{} ({}:{})",
+                        decode_word(*word).unwrap_or_else(|| "undecodable".to_string()),
+                        self.get_file_name(position.file),
+                        position.line
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    // Only the non-zero static data is listed, the rest of the RAM starts cleared
+    fn ram_bytes(&self) -> Vec<RamByte> {
+        let mut labels_by_address: HashMap<usize, Vec<&String>> = HashMap::new();
+        for (label, address) in self.symbol_table.iter() {
+            labels_by_address.entry(*address as usize).or_default().push(label);
+        }
+
+        self.data_memory[..self.static_data_end]
+            .iter()
+            .enumerate()
+            .filter(|(address, byte)| **byte != 0 || labels_by_address.contains_key(address))
+            .map(|(address, byte)| RamByte {
+                address,
+                byte: *byte,
+                comment: labels_by_address.get(&address).map(|labels| {
+                    let mut labels: Vec<&str> = labels.iter().map(|label| label.as_str()).collect();
+                    labels.sort();
+                    labels.join(", ")
+                }),
+            })
+            .collect()
+    }
+}
+
+impl RomGeneratable for Moonlight {
+    fn generate_rom(&self, ast: &Ast, module_name: &str, format: ImageFormat) -> String {
+        let rom_words = self.rom_words(ast);
+        let ram_bytes = self.ram_bytes();
+        let rom_depth = self.machine_profile.instruction_memory_size;
+        let ram_depth = self.data_memory.len();
+
+        let mut lines: Vec<String> = Vec::new();
+        match format {
+            ImageFormat::SvRom => {
+                lines.push(format!("// Generated by moonlight from {}", self.get_file_name(0)));
+                lines.push(String::new());
+                lines.push(format!("module {}_rom #(", module_name));
+                lines.push("    parameter int WIDTH = 16,".to_string());
+                lines.push(format!("    parameter int DEPTH = {}", rom_depth));
+                lines.push(") (".to_string());
+                lines.push("    input  logic                     clk,".to_string());
+                lines.push("    input  logic [$clog2(DEPTH)-1:0] addr,".to_string());
+                lines.push("    output logic [WIDTH-1:0]         data".to_string());
+                lines.push(");".to_string());
+                lines.push("    logic [WIDTH-1:0] rom [0:DEPTH-1];".to_string());
+                lines.push(String::new());
+                lines.push("    initial begin".to_string());
+                lines.push("        for (int i = 0; i < DEPTH; i++) rom[i] = '0;".to_string());
+                for (address, rom_word) in rom_words.iter().enumerate() {
+                    lines.push(format!(
+                        "        rom[{}] = WIDTH'(16'h{:04x}); // {}",
+                        address, rom_word.word, rom_word.comment
+                    ));
+                }
+                lines.push("    end".to_string());
+                lines.push(String::new());
+                lines.push("    always_ff @(posedge clk) data <= rom[addr];".to_string());
+                lines.push("endmodule".to_string());
+                lines.push(String::new());
+
+                lines.push(format!("module {}_ram #(", module_name));
+                lines.push(format!("    parameter int DEPTH = {}", ram_depth));
+                lines.push(") (".to_string());
+                lines.push("    input  logic                     clk,".to_string());
+                lines.push("    input  logic                     we,".to_string());
+                lines.push("    input  logic [$clog2(DEPTH)-1:0] addr,".to_string());
+                lines.push("    input  logic [7:0]               din,".to_string());
+                lines.push("    output logic [7:0]               dout".to_string());
+                lines.push(");".to_string());
+                lines.push("    logic [7:0] ram [0:DEPTH-1];".to_string());
+                lines.push(String::new());
+                lines.push("    initial begin".to_string());
+                lines.push("        for (int i = 0; i < DEPTH; i++) ram[i] = '0;".to_string());
+                for ram_byte in ram_bytes.iter() {
+                    match ram_byte.comment {
+                        Some(ref comment) => lines.push(format!(
+                            "        ram[{}] = 8'h{:02x}; // {}",
+                            ram_byte.address, ram_byte.byte, comment
+                        )),
+                        None => lines.push(format!("        ram[{}] = 8'h{:02x};", ram_byte.address, ram_byte.byte)),
+                    }
+                }
+                lines.push("    end".to_string());
+                lines.push(String::new());
+                lines.push("    always_ff @(posedge clk) begin".to_string());
+                lines.push("        if (we) ram[addr] <= din;".to_string());
+                lines.push("        dout <= ram[addr];".to_string());
+                lines.push("    end".to_string());
+                lines.push("endmodule".to_string());
+            }
+            ImageFormat::VhdlRom => {
+                lines.push(format!("-- Generated by moonlight from {}", self.get_file_name(0)));
+                lines.push(String::new());
+                lines.push("library ieee;".to_string());
+                lines.push("use ieee.std_logic_1164.all;".to_string());
+                lines.push("use ieee.numeric_std.all;".to_string());
+                lines.push(String::new());
+                lines.push(format!("entity {}_rom is", module_name));
+                lines.push("    generic (".to_string());
+                lines.push("        WIDTH      : natural := 16;".to_string());
+                lines.push(format!("        DEPTH      : natural := {};", rom_depth));
+                lines.push(format!("        ADDR_WIDTH : natural := {}", address_width(rom_depth)));
+                lines.push("    );".to_string());
+                lines.push("    port (".to_string());
+                lines.push("        clk  : in  std_logic;".to_string());
+                lines.push("        addr : in  unsigned(ADDR_WIDTH - 1 downto 0);".to_string());
+                lines.push("        data : out std_logic_vector(WIDTH - 1 downto 0)".to_string());
+                lines.push("    );".to_string());
+                lines.push("end entity;".to_string());
+                lines.push(String::new());
+                lines.push(format!("architecture rtl of {}_rom is", module_name));
+                lines.push("    type rom_t is array (0 to DEPTH - 1) of std_logic_vector(WIDTH - 1 downto 0);".to_string());
+                lines.push("    constant ROM : rom_t := (".to_string());
+                for (address, rom_word) in rom_words.iter().enumerate() {
+                    lines.push(format!(
+                        "        {} => std_logic_vector(to_unsigned(16#{:04x}#, WIDTH)), -- {}",
+                        address, rom_word.word, rom_word.comment
+                    ));
+                }
+                lines.push("        others => (others => '0')".to_string());
+                lines.push("    );".to_string());
+                lines.push("begin".to_string());
+                lines.push("    process (clk)".to_string());
+                lines.push("    begin".to_string());
+                lines.push("        if rising_edge(clk) then".to_string());
+                lines.push("            data <= ROM(to_integer(addr));".to_string());
+                lines.push("        end if;".to_string());
+                lines.push("    end process;".to_string());
+                lines.push("end architecture;".to_string());
+                lines.push(String::new());
+
+                lines.push("library ieee;".to_string());
+                lines.push("use ieee.std_logic_1164.all;".to_string());
+                lines.push("use ieee.numeric_std.all;".to_string());
+                lines.push(String::new());
+                lines.push(format!("entity {}_ram is", module_name));
+                lines.push("    generic (".to_string());
+                lines.push(format!("        DEPTH      : natural := {};", ram_depth));
+                lines.push(format!("        ADDR_WIDTH : natural := {}", address_width(ram_depth)));
+                lines.push("    );".to_string());
+                lines.push("    port (".to_string());
+                lines.push("        clk  : in  std_logic;".to_string());
+                lines.push("        we   : in  std_logic;".to_string());
+                lines.push("        addr : in  unsigned(ADDR_WIDTH - 1 downto 0);".to_string());
+                lines.push("        din  : in  std_logic_vector(7 downto 0);".to_string());
+                lines.push("        dout : out std_logic_vector(7 downto 0)".to_string());
+                lines.push("    );".to_string());
+                lines.push("end entity;".to_string());
+                lines.push(String::new());
+                lines.push(format!("architecture rtl of {}_ram is", module_name));
+                lines.push("    type ram_t is array (0 to DEPTH - 1) of std_logic_vector(7 downto 0);".to_string());
+                lines.push("    signal ram : ram_t := (".to_string());
+                for ram_byte in ram_bytes.iter() {
+                    match ram_byte.comment {
+                        Some(ref comment) => lines.push(format!(
+                            "        {} => x\"{:02x}\", -- {}",
+                            ram_byte.address, ram_byte.byte, comment
+                        )),
+                        None => lines.push(format!("        {} => x\"{:02x}\",", ram_byte.address, ram_byte.byte)),
+                    }
+                }
+                lines.push("        others => (others => '0')".to_string());
+                lines.push("    );".to_string());
+                lines.push("begin".to_string());
+                lines.push("    process (clk)".to_string());
+                lines.push("    begin".to_string());
+                lines.push("        if rising_edge(clk) then".to_string());
+                lines.push("            if we = '1' then".to_string());
+                lines.push("                ram(to_integer(addr)) <= din;".to_string());
+                lines.push("            end if;".to_string());
+                lines.push("            dout <= ram(to_integer(addr));".to_string());
+                lines.push("        end if;".to_string());
+                lines.push("    end process;".to_string());
+                lines.push("end architecture;".to_string());
+            }
+            ImageFormat::Bin | ImageFormat::Hex => unreachable!(),
+        }
+
+        let mut rom = lines.join("\n");
+        rom.push('\n');
+        rom
+    }
+}
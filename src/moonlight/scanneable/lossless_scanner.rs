@@ -0,0 +1,110 @@
+use crate::moonlight::utils::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LosslessTokenKind {
+    Word,
+    StringLiteral,
+    Punctuation,
+    Whitespace,
+    Comment,
+    Newline,
+}
+
+#[derive(Debug, Clone)]
+pub struct LosslessToken {
+    pub kind: LosslessTokenKind,
+    pub text: String,
+    pub position: Position,
+}
+
+impl LosslessToken {
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self.kind,
+            LosslessTokenKind::Whitespace | LosslessTokenKind::Comment | LosslessTokenKind::Newline
+        )
+    }
+}
+
+/*
+    Unlike scan_string_and_generate_positioned_tokens, this keeps every
+    character of the source: concatenating the text of the returned
+    tokens gives back the exact source. Words are not classified, so it
+    never fails, even on sources the parser would reject.
+ */
+pub fn scan_string_and_generate_lossless_tokens(source: &str, file_id: u32) -> Vec<LosslessToken> {
+    let mut tokens: Vec<LosslessToken> = Vec::new();
+    let chars: Vec<char> = source.chars().collect();
+
+    let mut line: u32 = 1;
+    let mut column: u32 = 1;
+    let mut index = 0;
+
+    while index < chars.len() {
+        let start = index;
+        let ch = chars[index];
+
+        let kind = match ch {
+            '\n' => {
+                index += 1;
+                LosslessTokenKind::Newline
+            }
+            ' ' | '\t' | '\r' => {
+                while index < chars.len() && matches!(chars[index], ' ' | '\t' | '\r') {
+                    index += 1;
+                }
+                LosslessTokenKind::Whitespace
+            }
+            '#' => {
+                while index < chars.len() && chars[index] != '\n' {
+                    index += 1;
+                }
+                LosslessTokenKind::Comment
+            }
+            '"' => {
+                // An unterminated string literal ends with its line
+                index += 1;
+                let mut is_escaped = false;
+                while index < chars.len() && chars[index] != '\n' {
+                    let current = chars[index];
+                    index += 1;
+                    match current {
+                        '\\' if !is_escaped => is_escaped = true,
+                        '"' if !is_escaped => break,
+                        _ => is_escaped = false,
+                    }
+                }
+                LosslessTokenKind::StringLiteral
+            }
+            ',' | '[' | ']' => {
+                index += 1;
+                LosslessTokenKind::Punctuation
+            }
+            _ => {
+                // Same word boundaries as the scanner, a ':' closes a label declaration
+                while index < chars.len() && !matches!(chars[index], '\n' | ' ' | '\t' | '\r' | '#' | '"' | ',' | '[' | ']') {
+                    index += 1;
+                    if chars[index - 1] == ':' {
+                        break;
+                    }
+                }
+                LosslessTokenKind::Word
+            }
+        };
+
+        tokens.push(LosslessToken {
+            kind: kind.clone(),
+            text: chars[start..index].iter().collect(),
+            position: Position::new(file_id, line, Some(column)),
+        });
+
+        if kind == LosslessTokenKind::Newline {
+            line += 1;
+            column = 1;
+        } else {
+            column += (index - start) as u32;
+        }
+    }
+
+    tokens
+}
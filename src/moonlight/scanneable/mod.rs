@@ -1,6 +1,10 @@
 pub mod scanner;
 pub mod scannable;
 pub mod positioned_token_vectorable;
+pub mod lossless_scanner;
+pub mod source_provider;
 
 pub use scanner::*;
-pub use scannable::*;
\ No newline at end of file
+pub use scannable::*;
+pub use lossless_scanner::*;
+pub use source_provider::*;
\ No newline at end of file
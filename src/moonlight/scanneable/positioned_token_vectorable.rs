@@ -1,14 +1,22 @@
+use std::collections::HashMap;
+
 use crate::moonlight::utils::*;
 
 pub trait PositionedTokenVectorable {
-    fn contexted_push(&mut self, token_string: String, file_id: u32, line: u32, column: Option<u32>) -> Result<(), String>;
+    fn contexted_push(&mut self, token_string: String, defines: &HashMap<String, String>, file_id: u32, line: u32, column: Option<u32>) -> Result<(), String>;
 }
 
 impl PositionedTokenVectorable for Vec<PositionedToken> {
-    fn contexted_push(&mut self, token_string: String, file_id: u32, line: u32, column: Option<u32>) -> Result<(), String> {
+    fn contexted_push(&mut self, token_string: String, defines: &HashMap<String, String>, file_id: u32, line: u32, column: Option<u32>) -> Result<(), String> {
         
         let position = Position::new(file_id, line, column);
 
+        // A defined name is replaced by its value before being classified
+        let token_string = match defines.get(&token_string) {
+            Some(value) => value.clone(),
+            None => token_string,
+        };
+
         match Token::from_string(&token_string) {
             Ok(token) => {
                 let positioned_token = PositionedToken {
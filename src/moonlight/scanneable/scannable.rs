@@ -6,23 +6,25 @@ use crate::moonlight::utils::*;
 use crate::moonlight::*;
 
 use super::scanner::*;
+use super::source_provider::*;
 
 pub trait Scannable {
-    fn scan(&mut self, file_path: &str) -> Vec<PositionedToken>;
-    fn resolve_includes(&mut self, file_path: &str, file_id: u32, file_counter: &mut u32, file_dependencies: &mut HashMap<u32, Vec<u32>>) -> Vec<PositionedToken>;
+    fn scan(&mut self, file_path: &str, source_provider: &dyn SourceProvider) -> Result<Vec<PositionedToken>, Diagnostic>;
+    fn resolve_includes(&mut self, file_path: &str, file_id: u32, file_counter: &mut u32, file_dependencies: &mut HashMap<u32, Vec<u32>>, source_provider: &dyn SourceProvider) -> Result<Vec<PositionedToken>, Diagnostic>;
     fn has_dependency_cycle(&self, file_dependencies: &HashMap<u32, Vec<u32>>, start: u32, target: u32) -> bool;
+    fn find_include_path(&self, path: &str, source_provider: &dyn SourceProvider) -> String;
 }
 
 impl Scannable for Moonlight {
-    fn scan(&mut self, file_path: &str) -> Vec<PositionedToken> {
+    fn scan(&mut self, file_path: &str, source_provider: &dyn SourceProvider) -> Result<Vec<PositionedToken>, Diagnostic> {
         let mut file_counter: u32 = 0;
         let mut file_dependencies: HashMap<u32, Vec<u32>> = HashMap::new();
 
-        let raw_tokens = self.resolve_includes(file_path, file_counter, &mut file_counter, &mut file_dependencies);
+        let raw_tokens = self.resolve_includes(file_path, file_counter, &mut file_counter, &mut file_dependencies, source_provider);
         raw_tokens
     }
 
-    fn resolve_includes(&mut self, file_path: &str, file_id: u32, file_counter: &mut u32, file_dependencies: &mut HashMap<u32, Vec<u32>>) -> Vec<PositionedToken> {
+    fn resolve_includes(&mut self, file_path: &str, file_id: u32, file_counter: &mut u32, file_dependencies: &mut HashMap<u32, Vec<u32>>, source_provider: &dyn SourceProvider) -> Result<Vec<PositionedToken>, Diagnostic> {
         /*
            Aqui vamos fazer um scan do arquivo file_path,
            verificar se existe alguma diretiva de include,
@@ -31,15 +33,14 @@ impl Scannable for Moonlight {
         */
         self.file_table.insert(file_id, file_path.to_string());
 
-        let mut tokens = match scan_tokens_from_file(file_path, file_id) {
+        let scanned_tokens = match source_provider.read_source(file_path) {
+            Ok(source) => scan_string_and_generate_positioned_tokens(&source.replace("\r", ""), file_id, &self.defines),
+            Err(e) => Err((e, None)),
+        };
+        let mut tokens = match scanned_tokens {
             Ok(tokens) => tokens,
-            Err((e, p)) => {
-                match p {
-                    Some(position) => self.exit_with_positional_error(e.as_str(), position),
-                    None => self.exit_with_error(e.as_str()),
-                }
-                return Vec::new();
-            }
+            Err((e, Some(position))) => return Err(Diagnostic::positional_error(e.as_str(), position)),
+            Err((e, None)) => return Err(Diagnostic::error(e.as_str())),
         };
 
         let mut token_counter = 0;
@@ -54,6 +55,8 @@ impl Scannable for Moonlight {
                     Directive::Include => {
                         if let Some(next_token) = tokens.get(token_counter + 1) {
                             if let Token::StringLiteral(path) = next_token.token.clone() {
+                                let path = self.find_include_path(&path, source_provider);
+
                                 // Encontrar o id do arquivo incluído, se já existir no file_table
                                 let included_file_id = if let Some((&id, _)) =
                                     self.file_table.iter().find(|(_, v)| **v == path)
@@ -73,11 +76,10 @@ impl Scannable for Moonlight {
                                     let included_name = self.get_file_name(included_file_id);
                                     let current_name = self.get_file_name(file_id);
 
-                                    self.exit_with_error(&format!(
+                                    return Err(Diagnostic::error(&format!(
                                         "Include cycle detected [{} -> {}]",
                                         current_name, included_name
-                                    ));
-                                    return Vec::new();
+                                    )));
                                 }
 
                                 // Atualizar file_table se necessário
@@ -93,7 +95,7 @@ impl Scannable for Moonlight {
 
                                 // Recursivamente processar includes
                                 let included_tokens =
-                                    self.resolve_includes(&path, included_file_id, file_counter, file_dependencies);
+                                    self.resolve_includes(&path, included_file_id, file_counter, file_dependencies, source_provider)?;
 
                                 // Remover a diretiva de include e o path dos tokens
                                 tokens.remove(token_counter);
@@ -107,12 +109,16 @@ impl Scannable for Moonlight {
                                 }
                                 continue;
                             } else {
-                                self.exit_with_positional_error(
+                                return Err(Diagnostic::positional_error(
                                     "Expected a string literal after .include directive.",
                                     next_token.position,
-                                );
-                                return Vec::new();
+                                ));
                             }
+                        } else {
+                            return Err(Diagnostic::positional_error(
+                                "Expected a string literal after .include directive.",
+                                tk.position,
+                            ));
                         }
                     }
                     _ => {}
@@ -121,7 +127,7 @@ impl Scannable for Moonlight {
             token_counter += 1;
         }
 
-        return tokens;
+        Ok(tokens)
     }
 
     fn has_dependency_cycle(&self, file_dependencies: &HashMap<u32, Vec<u32>>, start: u32, target: u32) -> bool {
@@ -149,4 +155,23 @@ impl Scannable for Moonlight {
         let mut visited = std::collections::HashSet::new();
         visit(file_dependencies, target, start, &mut visited)
     }
-}
\ No newline at end of file
+
+    fn find_include_path(&self, path: &str, source_provider: &dyn SourceProvider) -> String {
+        /*
+            Paths are tried as written first, then under each include
+            directory. If none exists, the path is kept as written so
+            the usual "does not exist" error reports it.
+         */
+        if source_provider.exists(path) {
+            return path.to_string();
+        }
+        for include_dir in self.include_dirs.iter() {
+            let candidate = std::path::Path::new(include_dir).join(path);
+            let candidate = candidate.to_string_lossy().to_string();
+            if source_provider.exists(&candidate) {
+                return candidate;
+            }
+        }
+        path.to_string()
+    }
+}
@@ -1,34 +1,11 @@
+use std::collections::HashMap;
+
 use crate::moonlight::utils::*;
 
 use super::positioned_token_vectorable::*;
 
-fn read_file(path: &str) -> Result<String, String> {
-    use std::fs;
-
-    let raw = match fs::read_to_string(path) {
-        Ok(content) => Ok(content.replace("\r", "")),
-        Err(_) => Err(format!("The file {} does not exist or could not be read", path)),
-    };
-
-    raw
-
-}
-
-pub fn scan_tokens_from_file(file_path: &str, file_id: u32) -> Result<Vec<PositionedToken>, (String, Option<Position>)> {
-    let raw = match read_file(file_path) {
-        Ok(content) => content,
-        Err(e) => return Err((e, None))
-    };
-
-    
-    scan_string_and_generate_positioned_tokens(&raw, file_id)
-    
-} 
-
-
-
 #[allow(unused_variables)]
-fn scan_string_and_generate_positioned_tokens(source: &str, file_id: u32) -> Result<Vec<PositionedToken>, (String, Option<Position>)> {
+pub fn scan_string_and_generate_positioned_tokens(source: &str, file_id: u32, defines: &HashMap<String, String>) -> Result<Vec<PositionedToken>, (String, Option<Position>)> {
     let mut tokens: Vec<PositionedToken> = Vec::new();
     let mut token_accumulator = String::new();
     let mut chars = source.chars().peekable();
@@ -63,6 +40,7 @@ fn scan_string_and_generate_positioned_tokens(source: &str, file_id: u32) -> Res
                 if !token_accumulator.is_empty() && !is_string_literal_mode {
                     match tokens.contexted_push(
                         token_accumulator.clone(),
+                        defines,
                         file_id,
                         actual_line,
                         if !line_has_identation { Some(initial_token_column) } else { None },
@@ -87,6 +65,7 @@ fn scan_string_and_generate_positioned_tokens(source: &str, file_id: u32) -> Res
                     if !token_accumulator.is_empty() {
                         match tokens.contexted_push(
                             token_accumulator.clone(),
+                            defines,
                             file_id,
                             actual_line,
                             if !line_has_identation { Some(initial_token_column) } else { None },
@@ -117,6 +96,7 @@ fn scan_string_and_generate_positioned_tokens(source: &str, file_id: u32) -> Res
                 if !token_accumulator.is_empty() {
                     match tokens.contexted_push(
                         token_accumulator.clone(),
+                        defines,
                         file_id,
                         actual_line,
                         if !line_has_identation { Some(initial_token_column) } else { None },
@@ -141,6 +121,7 @@ fn scan_string_and_generate_positioned_tokens(source: &str, file_id: u32) -> Res
                     token_accumulator.push(ch);
                     match tokens.contexted_push(
                         token_accumulator.clone(),
+                        defines,
                         file_id,
                         actual_line,
                         if !line_has_identation { Some(initial_token_column) } else { None },
@@ -157,6 +138,7 @@ fn scan_string_and_generate_positioned_tokens(source: &str, file_id: u32) -> Res
                     if !token_accumulator.is_empty() {
                         match tokens.contexted_push(
                             token_accumulator.clone(),
+                            defines,
                             file_id,
                             actual_line,
                             if !line_has_identation { Some(initial_token_column) } else { None },
@@ -185,6 +167,7 @@ fn scan_string_and_generate_positioned_tokens(source: &str, file_id: u32) -> Res
                 if !token_accumulator.is_empty() {
                     match tokens.contexted_push(
                         token_accumulator.clone(),
+                        defines,
                         file_id,
                         actual_line,
                         if !line_has_identation { Some(initial_token_column) } else { None },
@@ -198,6 +181,7 @@ fn scan_string_and_generate_positioned_tokens(source: &str, file_id: u32) -> Res
                 }
                 match tokens.contexted_push(
                     ch.to_string(),
+                    defines,
                     file_id,
                     actual_line,
                     if !line_has_identation { Some(initial_token_column) } else { None },
@@ -231,6 +215,7 @@ fn scan_string_and_generate_positioned_tokens(source: &str, file_id: u32) -> Res
                 if !token_accumulator.is_empty() {
                     match tokens.contexted_push(
                         token_accumulator.clone(),
+                        defines,
                         file_id,
                         actual_line,
                         if !line_has_identation { Some(initial_token_column) } else { None },
@@ -260,6 +245,7 @@ fn scan_string_and_generate_positioned_tokens(source: &str, file_id: u32) -> Res
     if !token_accumulator.is_empty() && !is_string_literal_mode {
         match tokens.contexted_push(
             token_accumulator.clone(),
+            defines,
             file_id,
             actual_line,
             if !line_has_identation { Some(initial_token_column) } else { None },
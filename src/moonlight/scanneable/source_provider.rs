@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+/*
+    Where the scanner reads sources from, for the main file and for
+    every .include. Paths are passed exactly as written, so relative
+    paths are up to the provider.
+ */
+pub trait SourceProvider {
+    fn read_source(&self, path: &str) -> Result<String, String>;
+    fn exists(&self, path: &str) -> bool;
+}
+
+pub struct FileSystemSourceProvider;
+
+impl SourceProvider for FileSystemSourceProvider {
+    fn read_source(&self, path: &str) -> Result<String, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(content),
+            Err(_) => Err(format!("The file {} does not exist or could not be read", path)),
+        }
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        std::path::Path::new(path).is_file()
+    }
+}
+
+/*
+    Sources kept in memory, like unsaved editor buffers or a submission
+    in a grader. Paths it does not know go to the fallback, if any.
+ */
+pub struct InMemorySourceProvider<'a> {
+    sources: HashMap<String, String>,
+    fallback: Option<&'a dyn SourceProvider>,
+}
+
+impl<'a> InMemorySourceProvider<'a> {
+    pub fn new() -> Self {
+        InMemorySourceProvider {
+            sources: HashMap::new(),
+            fallback: None,
+        }
+    }
+
+    pub fn over(fallback: &'a dyn SourceProvider) -> Self {
+        InMemorySourceProvider {
+            sources: HashMap::new(),
+            fallback: Some(fallback),
+        }
+    }
+
+    pub fn insert(&mut self, path: &str, source: &str) {
+        self.sources.insert(path.to_string(), source.to_string());
+    }
+}
+
+impl Default for InMemorySourceProvider<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SourceProvider for InMemorySourceProvider<'_> {
+    fn read_source(&self, path: &str) -> Result<String, String> {
+        match (self.sources.get(path), &self.fallback) {
+            (Some(source), _) => Ok(source.clone()),
+            (None, Some(fallback)) => fallback.read_source(path),
+            (None, None) => Err(format!("The file {} does not exist or could not be read", path)),
+        }
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.sources.contains_key(path) || self.fallback.as_ref().is_some_and(|fallback| fallback.exists(path))
+    }
+}
@@ -0,0 +1,3 @@
+pub mod section_layoutable;
+
+pub use section_layoutable::*;
@@ -0,0 +1,153 @@
+use crate::moonlight::debugable::*;
+use crate::moonlight::parseable::*;
+use crate::moonlight::utils::*;
+use crate::moonlight::Moonlight;
+
+pub trait SectionLayoutable {
+    fn lay_out_sections(&mut self, ast: &mut Ast) -> Result<(), Diagnostic>;
+    fn load_section_layout_from_file(&mut self, file_path: &str) -> Result<(), Diagnostic>;
+}
+
+impl SectionLayoutable for Moonlight {
+    fn lay_out_sections(&mut self, ast: &mut Ast) -> Result<(), Diagnostic> {
+        /*
+            Sections named in the layout come first, in its order. The
+            others follow: data, rodata, the custom sections in the order
+            they first appear, and bss last so the initialised data stays
+            together.
+         */
+        let mut section_names: Vec<String> = Vec::new();
+        for data_camp in ast.data_field.iter() {
+            if !section_names.contains(&data_camp.section) {
+                section_names.push(data_camp.section.clone());
+            }
+        }
+        section_names.sort_by_key(|name| match name.as_str() {
+            DATA_SECTION => 0,
+            RODATA_SECTION => 1,
+            BSS_SECTION => 3,
+            _ => 2,
+        });
+
+        let mut layout: Vec<SectionLayoutEntry> = self.section_layout
+            .iter()
+            .filter(|entry| section_names.contains(&entry.name))
+            .cloned()
+            .collect();
+        for name in section_names.iter() {
+            if !layout.iter().any(|entry| entry.name == *name) {
+                layout.push(SectionLayoutEntry {
+                    name: name.clone(),
+                    start: None,
+                    read_only: name == RODATA_SECTION,
+                });
+            }
+        }
+
+        let mut data_field: Vec<DataCamp> = Vec::new();
+        let mut sections: Vec<SectionPlacement> = Vec::new();
+        let mut counter: usize = 0;
+        for entry in layout.iter() {
+            let first_camp_index = data_field.len();
+            data_field.extend(
+                ast.data_field
+                    .iter()
+                    .filter(|data_camp| data_camp.section == entry.name)
+                    .cloned()
+            );
+
+            let start = entry.start.unwrap_or(counter);
+            if entry.start.is_some() {
+                data_field[first_camp_index].address = Some(start);
+            }
+
+            let end = start + data_field[first_camp_index..].iter().map(|data_camp| data_camp.size()).sum::<usize>();
+            sections.push(SectionPlacement {
+                name: entry.name.clone(),
+                start,
+                end,
+                read_only: entry.read_only,
+                zero_initialised: entry.name == BSS_SECTION,
+            });
+            counter = end;
+        }
+
+        /*
+            Explicit starts need not grow in layout order, so sections are
+            compared by address. Empty sections take no room and never
+            overlap.
+         */
+        let mut by_start: Vec<&SectionPlacement> = sections.iter().filter(|placement| placement.start < placement.end).collect();
+        by_start.sort_by_key(|placement| placement.start);
+        for pair in by_start.windows(2) {
+            if pair[1].start < pair[0].end {
+                return Err(Diagnostic::error(&format!(
+                    "Section {} spans 0x{:04x} to 0x{:04x} and overlaps section {} which spans 0x{:04x} to 0x{:04x}",
+                    pair[1].name, pair[1].start, pair[1].end, pair[0].name, pair[0].start, pair[0].end
+                )));
+            }
+        }
+
+        ast.data_field = data_field;
+        self.sections = sections;
+        Ok(())
+    }
+
+    fn load_section_layout_from_file(&mut self, file_path: &str) -> Result<(), Diagnostic> {
+        /*
+            A layout has one section per line: its name, an optional start
+            address and an optional readonly flag.
+                data
+                rodata  0x2000  readonly
+                vectors 0x7f00  # comments are allowed
+         */
+        let raw = match std::fs::read_to_string(file_path) {
+            Ok(content) => content.replace("\r", ""),
+            Err(_) => return Err(Diagnostic::error(&format!("The file {} does not exist or could not be read", file_path))),
+        };
+
+        let file_id = self.file_table.len() as u32;
+        self.file_table.insert(file_id, file_path.to_string());
+
+        self.section_layout.clear();
+        for (line_index, line) in raw.lines().enumerate() {
+            let position = Position::new(file_id, line_index as u32 + 1, None);
+            let content = match line.split_once('#') {
+                Some((content, _)) => content,
+                None => line,
+            };
+
+            let mut fields = content.split_whitespace();
+            let name = match fields.next() {
+                Some(name) => name.trim_start_matches('.').to_string(),
+                None => continue,
+            };
+            if self.section_layout.iter().any(|entry| entry.name == name) {
+                return Err(Diagnostic::positional_error(&format!("Section {} is laid out twice", name), position));
+            }
+
+            let mut entry = SectionLayoutEntry {
+                read_only: name == RODATA_SECTION,
+                name,
+                start: None,
+            };
+            for field in fields {
+                match field {
+                    "readonly" => entry.read_only = true,
+                    _ => match Token::from_string(&field.to_string()) {
+                        Ok(Token::Number(number)) if entry.start.is_none() => match number.to_u16() {
+                            Ok(start) => entry.start = Some(start as usize),
+                            Err(e) => return Err(Diagnostic::positional_error(e.as_str(), position)),
+                        },
+                        _ => return Err(Diagnostic::positional_error(
+                            "Expected a section name, an optional start address and an optional readonly flag",
+                            position,
+                        )),
+                    },
+                }
+            }
+            self.section_layout.push(entry);
+        }
+        Ok(())
+    }
+}
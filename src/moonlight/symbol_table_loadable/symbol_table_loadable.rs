@@ -4,13 +4,16 @@ use crate::moonlight::utils::*;
 use crate::moonlight::Moonlight;
 
 pub trait SymbolTableLoadable {
-    fn load_symbol_table_from(&mut self, ast: &Ast);
+    fn load_symbol_table_from(&mut self, ast: &Ast) -> Result<(), Diagnostic>;
 }
 
 impl SymbolTableLoadable for Moonlight {
-    fn load_symbol_table_from(&mut self, ast: &Ast) {
+    fn load_symbol_table_from(&mut self, ast: &Ast) -> Result<(), Diagnostic> {
         let mut stack_counter: usize = 0;
         for data_camp in ast.data_field.iter() {
+            if let Some(address) = data_camp.address {
+                stack_counter = address;
+            }
             for label in data_camp.label_declarations.iter() {
                 match label.token {
                     Token::LabelDeclaration(ref label_string) => {
@@ -22,7 +25,7 @@ impl SymbolTableLoadable for Moonlight {
                                 );
                             }
                             Err(_) => {
-                                self.exit_with_error("Stack overflow while loading symbol table.");
+                                return Err(Diagnostic::error("Stack overflow while loading symbol table."));
                             }
                         }
                     }
@@ -53,7 +56,7 @@ impl SymbolTableLoadable for Moonlight {
                                         stack_counter = v;
                                     }
                                     None => {
-                                        self.exit_with_error("Stack overflow while loading symbol table.");
+                                        return Err(Diagnostic::error("Stack overflow while loading symbol table."));
                                     }
                                 }
                             }
@@ -79,11 +82,11 @@ impl SymbolTableLoadable for Moonlight {
                                                     stack_counter = v;
                                                 }
                                                 None => {
-                                                    self.exit_with_error("Stack overflow while loading symbol table.");
+                                                    return Err(Diagnostic::error("Stack overflow while loading symbol table."));
                                                 }
                                             }
                                         }
-                                        Err(e) => self.exit_with_positional_error(e.as_str(), ptk.position),
+                                        Err(e) => return Err(Diagnostic::positional_error(e.as_str(), ptk.position)),
                                     }
                                 }
                                 _ => unreachable!(),
@@ -95,9 +98,16 @@ impl SymbolTableLoadable for Moonlight {
                 _ => unreachable!(),
             }
 
-
-            
+            if stack_counter > self.data_memory.len() {
+                return Err(Diagnostic::positional_error(
+                    &format!(
+                        "Stack overflow while loading symbol table: the data field needs more than the {} bytes of data memory.",
+                        self.data_memory.len()
+                    ),
+                    data_camp.directive.position,
+                ));
+            }
         }
+        Ok(())
     }
-
-}
\ No newline at end of file
+}
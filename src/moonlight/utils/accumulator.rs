@@ -4,4 +4,34 @@ pub enum Accumulator {
     Ac1,
     Ac2,
     Ac3,
-}
\ No newline at end of file
+}
+
+impl Accumulator {
+    pub fn from_index(index: u8) -> Option<Self> {
+        match index {
+            0 => Some(Accumulator::Ac0),
+            1 => Some(Accumulator::Ac1),
+            2 => Some(Accumulator::Ac2),
+            3 => Some(Accumulator::Ac3),
+            _ => None,
+        }
+    }
+
+    pub fn index(&self) -> u8 {
+        match self {
+            Accumulator::Ac0 => 0,
+            Accumulator::Ac1 => 1,
+            Accumulator::Ac2 => 2,
+            Accumulator::Ac3 => 3,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Accumulator::Ac0 => "&0",
+            Accumulator::Ac1 => "&1",
+            Accumulator::Ac2 => "&2",
+            Accumulator::Ac3 => "&3",
+        }
+    }
+}
@@ -7,4 +7,68 @@ pub enum Directive {
     Ascii,
     Byte,
     Inst,
-}
\ No newline at end of file
+    Rodata,
+    Bss,
+    Section,
+    Raw, // A raw instruction word in the instruction field
+}
+
+// The directives the parser accepts, .ascii is scanned but not supported yet
+pub const DIRECTIVES: [Directive; 10] = [
+    Directive::Include,
+    Directive::Data,
+    Directive::Rodata,
+    Directive::Bss,
+    Directive::Section,
+    Directive::Inst,
+    Directive::Space,
+    Directive::Word,
+    Directive::Byte,
+    Directive::Raw,
+];
+
+impl Directive {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Directive::Include => ".include",
+            Directive::Data => ".data",
+            Directive::Space => ".space",
+            Directive::Word => ".word",
+            Directive::Ascii => ".ascii",
+            Directive::Byte => ".byte",
+            Directive::Inst => ".inst",
+            Directive::Rodata => ".rodata",
+            Directive::Bss => ".bss",
+            Directive::Section => ".section",
+            Directive::Raw => ".raw",
+        }
+    }
+
+    // Same forms accepted by the parser
+    pub fn syntax(&self) -> &'static str {
+        match self {
+            Directive::Include => "\"path\"",
+            Directive::Section => "\"name\"",
+            Directive::Space => "size",
+            Directive::Word | Directive::Byte | Directive::Raw => "number, ...",
+            Directive::Ascii => "\"text\"",
+            Directive::Data | Directive::Inst | Directive::Rodata | Directive::Bss => "",
+        }
+    }
+
+    pub fn summary(&self) -> &'static str {
+        match self {
+            Directive::Include => "Include another source file",
+            Directive::Data => "Start the data field, in the data section",
+            Directive::Space => "Reserve bytes of data memory",
+            Directive::Word => "Declare 16-bit words",
+            Directive::Ascii => "Declare a string",
+            Directive::Byte => "Declare bytes",
+            Directive::Inst => "Start the instruction field",
+            Directive::Rodata => "Start the data field, in the read-only rodata section",
+            Directive::Bss => "Start the data field, in the zeroed bss section",
+            Directive::Section => "Start the data field, in a named section",
+            Directive::Raw => "Write raw instruction words",
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::moonlight::utils::parse_size;
+
+/*
+    What data memory holds before the program writes to it. The default
+    is zero; a random fill with a seed shows reads of bytes that were
+    never written, as on real hardware.
+ */
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum FillPattern {
+    #[default]
+    Zero,
+    Random(u64), // Seed
+    Constant(u8),
+}
+
+impl FillPattern {
+    // zero, random:<seed> or const:<value>, as written after --fill
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.split_once(':') {
+            None if name == "zero" => Ok(FillPattern::Zero),
+            Some(("random", seed)) => Ok(FillPattern::Random(parse_size(seed)? as u64)),
+            Some(("const", value)) => match u8::try_from(parse_size(value)?) {
+                Ok(value) => Ok(FillPattern::Constant(value)),
+                Err(_) => Err(format!("The fill value {} does not fit in a byte", value)),
+            },
+            _ => Err(format!("Unknown fill pattern {}, expected zero, random:<seed> or const:<value>", name)),
+        }
+    }
+
+    pub fn fill(&self, memory: &mut [u8]) {
+        match self {
+            FillPattern::Zero => memory.fill(0),
+            FillPattern::Constant(value) => memory.fill(*value),
+            FillPattern::Random(seed) => {
+                // xorshift64*, a zero state would only ever produce zeros
+                let mut state = if *seed == 0 { 0x9E3779B97F4A7C15 } else { *seed };
+                for memory_cell in memory.iter_mut() {
+                    state ^= state >> 12;
+                    state ^= state << 25;
+                    state ^= state >> 27;
+                    *memory_cell = (state.wrapping_mul(0x2545F4914F6CDD1D) >> 56) as u8;
+                }
+            }
+        }
+    }
+}
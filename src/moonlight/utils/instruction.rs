@@ -1,58 +1,97 @@
+use crate::moonlight::utils::*;
+
+/*
+    The opcode, mnemonic and operand format of every instruction
+    live in the ISA table (see isa.rs), not here.
+ */
 #[derive(Debug, Clone, PartialEq)]
 pub enum Instruction {
-    Nope = 0b000000,
-    Add = 0b000001,
-    Sub = 0b000010,
-    Not = 0b000011,
-    And = 0b000100,
-    Or = 0b000101,
-    Xor = 0b000110,
-    Nand = 0b000111,
-    Nor = 0b001000,
-    Xnor = 0b001001,
-
-    Slt = 0b010101,
-    Tmul = 0b001101,
-    Tdiv = 0b001110,
-
-    Sll = 0b001010,
-    Srl = 0b001011,
-    Sra = 0b001100,
-
-    Mtl = 0b001111,
-    Mfl = 0b010000,
-    Mth = 0b010001,
-    Mfh = 0b010010,
-    Mtac = 0b010011,
-    Mfac = 0b010100,
-
-    Addi = 0b010110,
-    Subi = 0b010111,
-    Andi = 0b011000,
-    Ori = 0b011001,
-    Xori = 0b011010,
-    Nandi = 0b011011,
-    Nori = 0b011100,
-    Xnori = 0b011101,
-    Lli = 0b011110,
-    Lui = 0b011111,
-    Lsi = 0b100000,
-
-    Lwr = 0b100001,
-    Swr = 0b100010,
-    Push = 0b100011,
-    Pop = 0b100100,
-
-    Jr = 0b100101,
-    Jrl = 0b100110,
-    Ja = 0b100111,
-    Jal = 0b101000,
-    Bgtz = 0b101001,
-    Bltz = 0b101010,
-    Beqz = 0b101011,
-    Bnez = 0b101100,
-    Bgtzr = 0b101101,
-    Bltzr = 0b101110,
-    Beqzr = 0b101111,
-    Bnezr = 0b110000,
-}
\ No newline at end of file
+    Nope,
+    Add,
+    Sub,
+    Not,
+    And,
+    Or,
+    Xor,
+    Nand,
+    Nor,
+    Xnor,
+
+    Slt,
+    Tmul,
+    Tdiv,
+
+    Sll,
+    Srl,
+    Sra,
+
+    Mtl,
+    Mfl,
+    Mth,
+    Mfh,
+    Mtac,
+    Mfac,
+
+    Addi,
+    Subi,
+    Andi,
+    Ori,
+    Xori,
+    Nandi,
+    Nori,
+    Xnori,
+    Lli,
+    Lui,
+    Lsi,
+
+    Lwr,
+    Swr,
+    Push,
+    Pop,
+
+    Jr,
+    Jrl,
+    Ja,
+    Jal,
+    Bgtz,
+    Bltz,
+    Beqz,
+    Bnez,
+    Bgtzr,
+    Bltzr,
+    Beqzr,
+    Bnezr,
+}
+
+impl Instruction {
+    pub fn spec(&self) -> &'static InstructionSpec {
+        match ISA.iter().find(|spec| spec.instruction == *self) {
+            Some(spec) => spec,
+            None => unreachable!(),
+        }
+    }
+
+    pub fn from_opcode(opcode: u8) -> Option<Self> {
+        InstructionSpec::from_opcode(opcode).map(|spec| spec.instruction.clone())
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+        InstructionSpec::from_mnemonic(mnemonic).map(|spec| spec.instruction.clone())
+    }
+
+    pub fn opcode(&self) -> u8 {
+        self.spec().opcode
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        self.spec().mnemonic
+    }
+
+    pub fn format(&self) -> InstructionFormat {
+        self.spec().format.clone()
+    }
+
+    pub fn has_signed_immediate(&self) -> bool {
+        self.spec().signed_immediate
+    }
+}
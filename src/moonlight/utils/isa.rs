@@ -0,0 +1,435 @@
+use crate::moonlight::utils::*;
+
+/*
+    PROVISIONAL: only the 6-bit opcodes come from the machine. How the
+    operands are packed after the opcode is a placeholder chosen by the
+    assembler until the hardware spec gives the real layout, so images
+    written with it, and disassemblies of real ROM dumps, may not match
+    the hardware.
+
+    Every instruction is a 16-bit word with the opcode in the 6 most
+    significant bits. The operands are packed right after it, from the
+    most to the least significant bit, in the order they are written:
+
+        AcRR      | opcode:6 | ac:2 | r1:4 | r2:4 |
+        AcR       | opcode:6 | ac:2 | r:4  | 0:4  |
+        R         | opcode:6 | r:4  | 0:6         |
+        AcRNumber | opcode:6 | ac:2 | r:4  | n:4  |
+        Ac        | opcode:6 | ac:2 | 0:8         |
+        AcNumber  | opcode:6 | ac:2 | n:8         |
+        Number    | opcode:6 | n:10               |
+        Empty     | opcode:6 | 0:10               |
+ */
+pub const OPCODE_SHIFT: u32 = 10;
+pub const OPCODE_WIDTH: u32 = 6;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstructionFormat {
+    Empty,
+    AcRR,
+    AcR,
+    R,
+    AcRNumber,
+    Ac,
+    AcNumber,
+    Number,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OperandKind {
+    Accumulator,
+    Register,
+    Immediate,
+}
+
+#[derive(Debug, Clone)]
+pub struct OperandField {
+    pub kind: OperandKind,
+    pub shift: u32,
+    pub width: u32,
+}
+
+impl OperandField {
+    const fn new(kind: OperandKind, shift: u32, width: u32) -> Self {
+        OperandField { kind, shift, width }
+    }
+
+    pub fn mask(&self) -> u16 {
+        (((1u32 << self.width) - 1) << self.shift) as u16
+    }
+}
+
+const AC_FIELD: OperandField = OperandField::new(OperandKind::Accumulator, 8, 2);
+
+impl InstructionFormat {
+    pub fn fields(&self) -> &'static [OperandField] {
+        const AC_R_R: [OperandField; 3] = [
+            AC_FIELD,
+            OperandField::new(OperandKind::Register, 4, 4),
+            OperandField::new(OperandKind::Register, 0, 4),
+        ];
+        const AC_R: [OperandField; 2] = [
+            AC_FIELD,
+            OperandField::new(OperandKind::Register, 4, 4),
+        ];
+        const R: [OperandField; 1] = [
+            OperandField::new(OperandKind::Register, 6, 4),
+        ];
+        const AC_R_NUMBER: [OperandField; 3] = [
+            AC_FIELD,
+            OperandField::new(OperandKind::Register, 4, 4),
+            OperandField::new(OperandKind::Immediate, 0, 4),
+        ];
+        const AC: [OperandField; 1] = [
+            AC_FIELD,
+        ];
+        const AC_NUMBER: [OperandField; 2] = [
+            AC_FIELD,
+            OperandField::new(OperandKind::Immediate, 0, 8),
+        ];
+        const NUMBER: [OperandField; 1] = [
+            OperandField::new(OperandKind::Immediate, 0, 10),
+        ];
+
+        match self {
+            InstructionFormat::Empty => &[],
+            InstructionFormat::AcRR => &AC_R_R,
+            InstructionFormat::AcR => &AC_R,
+            InstructionFormat::R => &R,
+            InstructionFormat::AcRNumber => &AC_R_NUMBER,
+            InstructionFormat::Ac => &AC,
+            InstructionFormat::AcNumber => &AC_NUMBER,
+            InstructionFormat::Number => &NUMBER,
+        }
+    }
+
+    // Number of tokens after the mnemonic, commas included
+    pub fn token_count(&self) -> usize {
+        match self.fields().len() {
+            0 => 0,
+            n => n * 2 - 1,
+        }
+    }
+
+    // The provisional field layout, see the top of this file
+    pub fn layout(&self) -> &'static str {
+        match self {
+            InstructionFormat::Empty => "| opcode:6 | 0:10 |",
+            InstructionFormat::AcRR => "| opcode:6 | ac:2 | r1:4 | r2:4 |",
+            InstructionFormat::AcR => "| opcode:6 | ac:2 | r:4 | 0:4 |",
+            InstructionFormat::R => "| opcode:6 | r:4 | 0:6 |",
+            InstructionFormat::AcRNumber => "| opcode:6 | ac:2 | r:4 | n:4 |",
+            InstructionFormat::Ac => "| opcode:6 | ac:2 | 0:8 |",
+            InstructionFormat::AcNumber => "| opcode:6 | ac:2 | n:8 |",
+            InstructionFormat::Number => "| opcode:6 | n:10 |",
+        }
+    }
+
+    pub fn syntax(&self) -> &'static str {
+        match self {
+            InstructionFormat::Empty => "",
+            InstructionFormat::AcRR => "&ac, $r, $r",
+            InstructionFormat::AcR => "&ac, $r",
+            InstructionFormat::R => "$r",
+            InstructionFormat::AcRNumber => "&ac, $r, number",
+            InstructionFormat::Ac => "&ac",
+            InstructionFormat::AcNumber => "&ac, number",
+            InstructionFormat::Number => "number",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InstructionSpec {
+    pub instruction: Instruction,
+    pub mnemonic: &'static str,
+    pub opcode: u8,
+    pub format: InstructionFormat,
+    pub signed_immediate: bool,
+    pub summary: &'static str,
+}
+
+const fn spec(instruction: Instruction, mnemonic: &'static str, opcode: u8, format: InstructionFormat, signed_immediate: bool, summary: &'static str) -> InstructionSpec {
+    InstructionSpec {
+        instruction,
+        mnemonic,
+        opcode,
+        format,
+        signed_immediate,
+        summary,
+    }
+}
+
+pub const ISA: [InstructionSpec; 49] = [
+    spec(Instruction::Nope, "nope", 0b000000, InstructionFormat::Empty, false, "No operation"),
+    spec(Instruction::Add, "add", 0b000001, InstructionFormat::AcRR, false, "Add"),
+    spec(Instruction::Sub, "sub", 0b000010, InstructionFormat::AcRR, false, "Subtract"),
+    spec(Instruction::Not, "not", 0b000011, InstructionFormat::AcR, false, "Bitwise NOT"),
+    spec(Instruction::And, "and", 0b000100, InstructionFormat::AcRR, false, "Bitwise AND"),
+    spec(Instruction::Or, "or", 0b000101, InstructionFormat::AcRR, false, "Bitwise OR"),
+    spec(Instruction::Xor, "xor", 0b000110, InstructionFormat::AcRR, false, "Bitwise XOR"),
+    spec(Instruction::Nand, "nand", 0b000111, InstructionFormat::AcRR, false, "Bitwise NAND"),
+    spec(Instruction::Nor, "nor", 0b001000, InstructionFormat::AcRR, false, "Bitwise NOR"),
+    spec(Instruction::Xnor, "xnor", 0b001001, InstructionFormat::AcRR, false, "Bitwise XNOR"),
+
+    spec(Instruction::Slt, "slt", 0b010101, InstructionFormat::AcRR, false, "Set on less than"),
+    spec(Instruction::Tmul, "tmul", 0b001101, InstructionFormat::R, false, "Multiply"),
+    spec(Instruction::Tdiv, "tdiv", 0b001110, InstructionFormat::R, false, "Divide"),
+
+    spec(Instruction::Sll, "sll", 0b001010, InstructionFormat::AcRNumber, false, "Shift left logical"),
+    spec(Instruction::Srl, "srl", 0b001011, InstructionFormat::AcRNumber, false, "Shift right logical"),
+    spec(Instruction::Sra, "sra", 0b001100, InstructionFormat::AcRNumber, false, "Shift right arithmetic"),
+
+    spec(Instruction::Mtl, "mtl", 0b001111, InstructionFormat::Ac, false, "Move to low"),
+    spec(Instruction::Mfl, "mfl", 0b010000, InstructionFormat::Ac, false, "Move from low"),
+    spec(Instruction::Mth, "mth", 0b010001, InstructionFormat::Ac, false, "Move to high"),
+    spec(Instruction::Mfh, "mfh", 0b010010, InstructionFormat::Ac, false, "Move from high"),
+    spec(Instruction::Mtac, "mtac", 0b010011, InstructionFormat::AcR, false, "Move to accumulator"),
+    spec(Instruction::Mfac, "mfac", 0b010100, InstructionFormat::AcR, false, "Move from accumulator"),
+
+    spec(Instruction::Addi, "addi", 0b010110, InstructionFormat::AcNumber, true, "Add immediate"),
+    spec(Instruction::Subi, "subi", 0b010111, InstructionFormat::AcNumber, true, "Subtract immediate"),
+    spec(Instruction::Andi, "andi", 0b011000, InstructionFormat::AcNumber, false, "Bitwise AND immediate"),
+    spec(Instruction::Ori, "ori", 0b011001, InstructionFormat::AcNumber, false, "Bitwise OR immediate"),
+    spec(Instruction::Xori, "xori", 0b011010, InstructionFormat::AcNumber, false, "Bitwise XOR immediate"),
+    spec(Instruction::Nandi, "nandi", 0b011011, InstructionFormat::AcNumber, false, "Bitwise NAND immediate"),
+    spec(Instruction::Nori, "nori", 0b011100, InstructionFormat::AcNumber, false, "Bitwise NOR immediate"),
+    spec(Instruction::Xnori, "xnori", 0b011101, InstructionFormat::AcNumber, false, "Bitwise XNOR immediate"),
+    spec(Instruction::Lli, "lli", 0b011110, InstructionFormat::AcNumber, false, "Load lower immediate"),
+    spec(Instruction::Lui, "lui", 0b011111, InstructionFormat::AcNumber, false, "Load upper immediate"),
+    spec(Instruction::Lsi, "lsi", 0b100000, InstructionFormat::AcNumber, true, "Load signed immediate"),
+
+    spec(Instruction::Lwr, "lwr", 0b100001, InstructionFormat::AcRR, false, "Load word from register address"),
+    spec(Instruction::Swr, "swr", 0b100010, InstructionFormat::AcRR, false, "Store word to register address"),
+    spec(Instruction::Push, "push", 0b100011, InstructionFormat::Ac, false, "Push onto the stack"),
+    spec(Instruction::Pop, "pop", 0b100100, InstructionFormat::Ac, false, "Pop from the stack"),
+
+    spec(Instruction::Jr, "jr", 0b100101, InstructionFormat::Number, true, "Jump relative"),
+    spec(Instruction::Jrl, "jrl", 0b100110, InstructionFormat::Number, true, "Jump relative and link"),
+    spec(Instruction::Ja, "ja", 0b100111, InstructionFormat::R, false, "Jump absolute"),
+    spec(Instruction::Jal, "jal", 0b101000, InstructionFormat::R, false, "Jump absolute and link"),
+    spec(Instruction::Bgtz, "bgtz", 0b101001, InstructionFormat::AcNumber, true, "Branch if greater than zero"),
+    spec(Instruction::Bltz, "bltz", 0b101010, InstructionFormat::AcNumber, true, "Branch if less than zero"),
+    spec(Instruction::Beqz, "beqz", 0b101011, InstructionFormat::AcNumber, true, "Branch if equal to zero"),
+    spec(Instruction::Bnez, "bnez", 0b101100, InstructionFormat::AcNumber, true, "Branch if not equal to zero"),
+    spec(Instruction::Bgtzr, "bgtzr", 0b101101, InstructionFormat::AcR, false, "Branch to register if greater than zero"),
+    spec(Instruction::Bltzr, "bltzr", 0b101110, InstructionFormat::AcR, false, "Branch to register if less than zero"),
+    spec(Instruction::Beqzr, "beqzr", 0b101111, InstructionFormat::AcR, false, "Branch to register if equal to zero"),
+    spec(Instruction::Bnezr, "bnezr", 0b110000, InstructionFormat::AcR, false, "Branch to register if not equal to zero"),
+];
+
+/*
+    PROVISIONAL, like the operand layout: jr, jrl and the b*z branches
+    are taken to land on the instruction at their own index plus their
+    offset. The machine's documentation does not say whether the offset
+    counts from the branch or from the instruction after it, so only the
+    disassembler uses this, to name the label a branch would reach. The
+    lints and the peephole pass treat numeric targets as unknown.
+ */
+pub fn relative_target(index: usize, offset: i32) -> Option<usize> {
+    usize::try_from(index as i64 + offset as i64).ok()
+}
+
+impl InstructionSpec {
+    pub fn from_opcode(opcode: u8) -> Option<&'static Self> {
+        ISA.iter().find(|spec| spec.opcode == opcode)
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Option<&'static Self> {
+        ISA.iter().find(|spec| spec.mnemonic == mnemonic)
+    }
+
+    pub fn from_word(word: u16) -> Option<&'static Self> {
+        Self::from_opcode((word >> OPCODE_SHIFT) as u8)
+    }
+
+    // jr, jrl and the b*z branches, whose immediate is an offset, see relative_target
+    pub fn has_relative_target(&self) -> bool {
+        matches!(
+            self.instruction,
+            Instruction::Jr | Instruction::Jrl | Instruction::Bgtz | Instruction::Bltz | Instruction::Beqz | Instruction::Bnez
+        )
+    }
+
+    // What each operand in the syntax stands for, in the same order
+    pub fn operand_descriptions(&self) -> Vec<String> {
+        let registers = self.format.fields().iter().filter(|field| field.kind == OperandKind::Register).count();
+        let mut register_index = 0;
+        self.format.fields()
+            .iter()
+            .map(|field| match field.kind {
+                OperandKind::Accumulator => "`&ac` accumulator, &0 to &3".to_string(),
+                OperandKind::Register => {
+                    register_index += 1;
+                    match (registers, register_index) {
+                        (1, _) => "`$r` register, $0 to $15".to_string(),
+                        (_, 1) => "`$r` first register, $0 to $15".to_string(),
+                        _ => "`$r` second register, $0 to $15".to_string(),
+                    }
+                }
+                OperandKind::Immediate => {
+                    let (min, max) = self.immediate_range(field.width);
+                    if self.has_relative_target() {
+                        format!("`number` offset to the target instruction, {} to {}", min, max)
+                    } else {
+                        format!("`number` {} immediate, {} to {}", if self.signed_immediate { "signed" } else { "unsigned" }, min, max)
+                    }
+                }
+            })
+            .collect()
+    }
+
+    pub fn immediate_range(&self, width: u32) -> (i32, i32) {
+        if self.signed_immediate {
+            (-(1 << (width - 1)), (1 << (width - 1)) - 1)
+        } else {
+            (0, (1 << width) - 1)
+        }
+    }
+
+    /*
+        Operands are given in the order they are written, accumulators
+        and registers by index and immediates by value.
+     */
+    pub fn encode(&self, operands: &[i32]) -> Result<u16, String> {
+        let fields = self.format.fields();
+        if operands.len() != fields.len() {
+            return Err(format!(
+                "Instruction {} expects {} operands, found {}.",
+                self.mnemonic,
+                fields.len(),
+                operands.len()
+            ));
+        }
+
+        let mut word = (self.opcode as u16) << OPCODE_SHIFT;
+        for (field, operand) in fields.iter().zip(operands.iter()) {
+            let (min, max) = match field.kind {
+                OperandKind::Immediate => self.immediate_range(field.width),
+                _ => (0, (1 << field.width) - 1),
+            };
+            if *operand < min || *operand > max {
+                return Err(format!(
+                    "Immediate {} out of range for instruction {}, expected a value between {} and {}.",
+                    operand, self.mnemonic, min, max
+                ));
+            }
+            word |= (((*operand as u32) << field.shift) as u16) & field.mask();
+        }
+
+        Ok(word)
+    }
+
+    // Returns None when the bits that no operand uses are not zero
+    pub fn decode(&self, word: u16) -> Option<Vec<i32>> {
+        let fields = self.format.fields();
+        let opcode_mask = (((1u32 << OPCODE_WIDTH) - 1) << OPCODE_SHIFT) as u16;
+        let used_mask = fields.iter().fold(opcode_mask, |mask, field| mask | field.mask());
+        if word & !used_mask != 0 {
+            return None;
+        }
+
+        Some(
+            fields
+                .iter()
+                .map(|field| {
+                    let raw = ((word & field.mask()) >> field.shift) as i32;
+                    if field.kind == OperandKind::Immediate && self.signed_immediate {
+                        let shift = 32 - field.width;
+                        (raw << shift) >> shift
+                    } else {
+                        raw
+                    }
+                })
+                .collect()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMATS: [InstructionFormat; 8] = [
+        InstructionFormat::Empty,
+        InstructionFormat::AcRR,
+        InstructionFormat::AcR,
+        InstructionFormat::R,
+        InstructionFormat::AcRNumber,
+        InstructionFormat::Ac,
+        InstructionFormat::AcNumber,
+        InstructionFormat::Number,
+    ];
+
+    // Operands at both ends of every field, immediates at the bounds of their range
+    fn operand_sets(spec: &InstructionSpec) -> Vec<Vec<i32>> {
+        let bounds: Vec<(i32, i32)> = spec.format
+            .fields()
+            .iter()
+            .map(|field| match field.kind {
+                OperandKind::Immediate => spec.immediate_range(field.width),
+                _ => (0, (1 << field.width) - 1),
+            })
+            .collect();
+        vec![
+            bounds.iter().map(|(min, _)| *min).collect(),
+            bounds.iter().map(|(_, max)| *max).collect(),
+            bounds.iter().map(|(min, max)| (min + max) / 2).collect(),
+        ]
+    }
+
+    #[test]
+    fn every_format_is_in_the_table() {
+        for format in FORMATS.iter() {
+            assert!(ISA.iter().any(|spec| spec.format == *format), "no instruction uses {:?}", format);
+        }
+    }
+
+    #[test]
+    fn encode_then_decode_gives_the_operands_back() {
+        for spec in ISA.iter() {
+            for operands in operand_sets(spec) {
+                let word = spec.encode(&operands).unwrap();
+                assert_eq!(InstructionSpec::from_word(word).map(|found| found.mnemonic), Some(spec.mnemonic));
+                assert_eq!(spec.decode(word), Some(operands.clone()), "{} {:?}", spec.mnemonic, operands);
+            }
+        }
+    }
+
+    #[test]
+    fn immediates_outside_their_range_are_rejected() {
+        for spec in ISA.iter() {
+            let fields = spec.format.fields();
+            let immediate = match fields.iter().position(|field| field.kind == OperandKind::Immediate) {
+                Some(index) => index,
+                None => continue,
+            };
+            let (min, max) = spec.immediate_range(fields[immediate].width);
+            for value in [min - 1, max + 1] {
+                let mut operands = vec![0; fields.len()];
+                operands[immediate] = value;
+                assert!(spec.encode(&operands).is_err(), "{} accepted {}", spec.mnemonic, value);
+            }
+        }
+    }
+
+    #[test]
+    fn immediate_bounds() {
+        let addi = InstructionSpec::from_mnemonic("addi").unwrap();
+        let andi = InstructionSpec::from_mnemonic("andi").unwrap();
+        let jr = InstructionSpec::from_mnemonic("jr").unwrap();
+        let sll = InstructionSpec::from_mnemonic("sll").unwrap();
+        assert_eq!(addi.immediate_range(8), (-128, 127));
+        assert_eq!(andi.immediate_range(8), (0, 255));
+        assert_eq!(jr.immediate_range(10), (-512, 511));
+        assert_eq!(sll.immediate_range(4), (0, 15));
+    }
+
+    #[test]
+    fn every_operand_in_the_syntax_is_described() {
+        for spec in ISA.iter() {
+            let operands = spec.format.syntax().split(", ").filter(|operand| !operand.is_empty()).count();
+            assert_eq!(spec.operand_descriptions().len(), operands, "{}", spec.mnemonic);
+        }
+    }
+}
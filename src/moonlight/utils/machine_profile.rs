@@ -0,0 +1,162 @@
+/*
+    The board a program is assembled for. Sizes are in bytes for data
+    memory and in 16-bit words for instruction memory.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct MachineProfile {
+    pub data_memory_size: usize,
+    pub instruction_memory_size: usize,
+    pub io_window: Option<(usize, usize)>, // Start address and size of the memory-mapped I/O bytes
+    pub initial_stack_pointer: usize,
+    pub reset_pc: usize,
+}
+
+pub const DEFAULT_DATA_MEMORY_SIZE: usize = 32768;
+pub const DEFAULT_INSTRUCTION_MEMORY_SIZE: usize = 32768;
+
+// Addresses are 16 bits wide, in bytes for data and in words for instructions
+const ADDRESS_SPACE_SIZE: usize = 65536;
+
+pub const MACHINE_PROFILE_KEYS: [&str; 5] = [
+    "data_memory_size",
+    "instruction_memory_size",
+    "io_window",
+    "initial_stack_pointer",
+    "reset_pc",
+];
+
+impl Default for MachineProfile {
+    fn default() -> Self {
+        MachineProfile {
+            data_memory_size: DEFAULT_DATA_MEMORY_SIZE,
+            instruction_memory_size: DEFAULT_INSTRUCTION_MEMORY_SIZE,
+            io_window: None,
+            initial_stack_pointer: DEFAULT_DATA_MEMORY_SIZE,
+            reset_pc: 0,
+        }
+    }
+}
+
+/*
+    Accepts decimal, 0x and 0b numbers, optionally followed by K or KiB
+    for multiples of 1024.
+ */
+pub(crate) fn parse_size(value: &str) -> Result<usize, String> {
+    let trimmed = value.trim();
+    let (digits, multiplier) = match trimmed.strip_suffix("KiB").or_else(|| trimmed.strip_suffix('K')) {
+        Some(digits) => (digits, 1024),
+        None => (trimmed, 1),
+    };
+
+    let parsed = if let Some(hex) = digits.strip_prefix("0x") {
+        usize::from_str_radix(hex, 16)
+    } else if let Some(bin) = digits.strip_prefix("0b") {
+        usize::from_str_radix(bin, 2)
+    } else {
+        digits.parse::<usize>()
+    };
+
+    match parsed.ok().and_then(|number| number.checked_mul(multiplier)) {
+        Some(size) => Ok(size),
+        None => Err(format!("Invalid number {}", value)),
+    }
+}
+
+impl MachineProfile {
+    /*
+        Sets one setting from its name, as written in a profile file.
+        Changing the data memory size also moves a stack pointer that
+        was still at the top of the old memory.
+     */
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "data_memory_size" => {
+                let size = parse_size(value)?;
+                if self.initial_stack_pointer == self.data_memory_size {
+                    self.initial_stack_pointer = size;
+                }
+                self.data_memory_size = size;
+            }
+            "instruction_memory_size" => self.instruction_memory_size = parse_size(value)?,
+            "io_window" => {
+                self.io_window = match value.trim() {
+                    "none" => None,
+                    window => match window.split_once(':') {
+                        Some((start, size)) => Some((parse_size(start)?, parse_size(size)?)),
+                        None => return Err(format!("Expected START:SIZE for io_window, found {}", value)),
+                    },
+                };
+            }
+            "initial_stack_pointer" => self.initial_stack_pointer = parse_size(value)?,
+            "reset_pc" => self.reset_pc = parse_size(value)?,
+            _ => {
+                return Err(format!(
+                    "Unknown machine profile setting {}, expected one of {}",
+                    key,
+                    MACHINE_PROFILE_KEYS.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /*
+        A profile file has one setting per line:
+            data_memory_size = 4K
+            io_window = 0x0f00:256    # comments are allowed
+        Settings that are not written keep their current value.
+     */
+    pub fn load_file(&mut self, file_path: &str) -> Result<(), String> {
+        let raw = match std::fs::read_to_string(file_path) {
+            Ok(content) => content.replace("\r", ""),
+            Err(_) => return Err(format!("The file {} does not exist or could not be read", file_path)),
+        };
+
+        for (line_index, line) in raw.lines().enumerate() {
+            let content = match line.split_once('#') {
+                Some((content, _)) => content,
+                None => line,
+            };
+            if content.trim().is_empty() {
+                continue;
+            }
+
+            match content.split_once('=') {
+                Some((key, value)) => {
+                    if let Err(e) = self.set(key.trim(), value) {
+                        return Err(format!("{} [file: {}, line: {}]", e, file_path, line_index + 1));
+                    }
+                }
+                None => {
+                    return Err(format!(
+                        "Expected a setting written as name = value [file: {}, line: {}]",
+                        file_path,
+                        line_index + 1
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.data_memory_size == 0 || self.data_memory_size > ADDRESS_SPACE_SIZE {
+            return Err(format!("The data memory size must be between 1 and {} bytes", ADDRESS_SPACE_SIZE));
+        }
+        if self.instruction_memory_size == 0 || self.instruction_memory_size > ADDRESS_SPACE_SIZE {
+            return Err(format!("The instruction memory size must be between 1 and {} words", ADDRESS_SPACE_SIZE));
+        }
+        if let Some((start, size)) = self.io_window
+            && start.checked_add(size).is_none_or(|end| end > self.data_memory_size)
+        {
+            return Err(format!("The I/O window 0x{:04x}:{} does not fit in data memory", start, size));
+        }
+        if self.initial_stack_pointer > self.data_memory_size {
+            return Err(format!("The initial stack pointer 0x{:04x} is outside data memory", self.initial_stack_pointer));
+        }
+        if self.reset_pc >= self.instruction_memory_size {
+            return Err(format!("The reset PC 0x{:04x} is outside instruction memory", self.reset_pc));
+        }
+        Ok(())
+    }
+}
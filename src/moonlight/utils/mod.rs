@@ -1,19 +1,27 @@
 pub mod token;
 pub mod position;
 pub mod instruction;
+pub mod isa;
 pub mod pseudo_instruction;
 pub mod directive;
 pub mod number;
 pub mod accumulator;
 pub mod register;
 pub mod stringable;
+pub mod fill_pattern;
+pub mod machine_profile;
+pub mod section_layout;
 
 pub use token::*;
 pub use position::*;
 pub use instruction::*;
+pub use isa::*;
 pub use pseudo_instruction::*;
 pub use directive::*;
 pub use number::*;
 pub use accumulator::*;
 pub use register::*;
 pub use stringable::*;
+pub use fill_pattern::*;
+pub use machine_profile::*;
+pub use section_layout::*;
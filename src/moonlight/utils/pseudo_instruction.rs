@@ -8,4 +8,58 @@ pub enum PseudoInstruction {
     Swap,
     Call,
     Ret,
-}
\ No newline at end of file
+}
+
+pub const PSEUDO_INSTRUCTIONS: [PseudoInstruction; 8] = [
+    PseudoInstruction::Jump,
+    PseudoInstruction::Lw,
+    PseudoInstruction::Sw,
+    PseudoInstruction::Mul,
+    PseudoInstruction::Div,
+    PseudoInstruction::Swap,
+    PseudoInstruction::Call,
+    PseudoInstruction::Ret,
+];
+
+impl PseudoInstruction {
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+        PSEUDO_INSTRUCTIONS.iter().find(|psinstr| psinstr.mnemonic() == mnemonic).cloned()
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            PseudoInstruction::Jump => "jump",
+            PseudoInstruction::Lw => "lw",
+            PseudoInstruction::Sw => "sw",
+            PseudoInstruction::Mul => "mul",
+            PseudoInstruction::Div => "div",
+            PseudoInstruction::Swap => "swap",
+            PseudoInstruction::Call => "call",
+            PseudoInstruction::Ret => "ret",
+        }
+    }
+
+    // Same formats accepted by the read_*_format functions of the parser
+    pub fn syntax(&self) -> &'static str {
+        match self {
+            PseudoInstruction::Jump => "_label|number",
+            PseudoInstruction::Lw | PseudoInstruction::Sw => "&ac, _label[number]",
+            PseudoInstruction::Mul | PseudoInstruction::Div | PseudoInstruction::Swap => "$r, $r",
+            PseudoInstruction::Call => "_label",
+            PseudoInstruction::Ret => "",
+        }
+    }
+
+    pub fn summary(&self) -> &'static str {
+        match self {
+            PseudoInstruction::Jump => "Jump to a label or address",
+            PseudoInstruction::Lw => "Load word from a label plus offset",
+            PseudoInstruction::Sw => "Store word to a label plus offset",
+            PseudoInstruction::Mul => "Multiply two registers",
+            PseudoInstruction::Div => "Divide two registers",
+            PseudoInstruction::Swap => "Swap two registers",
+            PseudoInstruction::Call => "Call the subroutine at a label",
+            PseudoInstruction::Ret => "Return from a subroutine",
+        }
+    }
+}
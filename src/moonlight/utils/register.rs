@@ -16,4 +16,70 @@ pub enum Register {
     Rf13,
     Rf14, // Stack Pointer Register
     Rf15, // Link Register
-}
\ No newline at end of file
+}
+
+impl Register {
+    pub fn from_index(index: u8) -> Option<Self> {
+        match index {
+            0 => Some(Register::Rf0),
+            1 => Some(Register::Rf1),
+            2 => Some(Register::Rf2),
+            3 => Some(Register::Rf3),
+            4 => Some(Register::Rf4),
+            5 => Some(Register::Rf5),
+            6 => Some(Register::Rf6),
+            7 => Some(Register::Rf7),
+            8 => Some(Register::Rf8),
+            9 => Some(Register::Rf9),
+            10 => Some(Register::Rf10),
+            11 => Some(Register::Rf11),
+            12 => Some(Register::Rf12),
+            13 => Some(Register::Rf13),
+            14 => Some(Register::Rf14),
+            15 => Some(Register::Rf15),
+            _ => None,
+        }
+    }
+
+    pub fn index(&self) -> u8 {
+        match self {
+            Register::Rf0 => 0,
+            Register::Rf1 => 1,
+            Register::Rf2 => 2,
+            Register::Rf3 => 3,
+            Register::Rf4 => 4,
+            Register::Rf5 => 5,
+            Register::Rf6 => 6,
+            Register::Rf7 => 7,
+            Register::Rf8 => 8,
+            Register::Rf9 => 9,
+            Register::Rf10 => 10,
+            Register::Rf11 => 11,
+            Register::Rf12 => 12,
+            Register::Rf13 => 13,
+            Register::Rf14 => 14,
+            Register::Rf15 => 15,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Register::Rf0 => "$0",
+            Register::Rf1 => "$1",
+            Register::Rf2 => "$2",
+            Register::Rf3 => "$3",
+            Register::Rf4 => "$4",
+            Register::Rf5 => "$5",
+            Register::Rf6 => "$6",
+            Register::Rf7 => "$7",
+            Register::Rf8 => "$8",
+            Register::Rf9 => "$9",
+            Register::Rf10 => "$10",
+            Register::Rf11 => "$11",
+            Register::Rf12 => "$12",
+            Register::Rf13 => "$13",
+            Register::Rf14 => "$14",
+            Register::Rf15 => "$15",
+        }
+    }
+}
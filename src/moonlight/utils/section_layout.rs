@@ -0,0 +1,24 @@
+/*
+    One line of a layout description: where a data section goes and
+    whether stores into it are allowed.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionLayoutEntry {
+    pub name: String,
+    pub start: Option<usize>, // Right after the previous section when not given
+    pub read_only: bool,
+}
+
+// Where a data section ended up once the data field was laid out
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionPlacement {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    pub read_only: bool,
+    pub zero_initialised: bool,
+}
+
+pub const DATA_SECTION: &str = "data";
+pub const RODATA_SECTION: &str = "rodata";
+pub const BSS_SECTION: &str = "bss";
@@ -23,63 +23,14 @@ pub enum Token {
 
 impl Token {
     pub fn from_string(token_string: &String) -> Result<Self, String> {
-        match token_string.as_str() {
-            "nope" => Ok(Token::Instruction(Instruction::Nope)),
-
-            "add" => Ok(Token::Instruction(Instruction::Add)),
-            "sub" => Ok(Token::Instruction(Instruction::Sub)),
-            "not" => Ok(Token::Instruction(Instruction::Not)),
-            "and" => Ok(Token::Instruction(Instruction::And)),
-            "or" => Ok(Token::Instruction(Instruction::Or)),
-            "xor" => Ok(Token::Instruction(Instruction::Xor)),
-            "nand" => Ok(Token::Instruction(Instruction::Nand)),
-            "nor" => Ok(Token::Instruction(Instruction::Nor)),
-            "xnor" => Ok(Token::Instruction(Instruction::Xnor)),
-            "slt" => Ok(Token::Instruction(Instruction::Slt)),
-            "tmul" => Ok(Token::Instruction(Instruction::Tmul)),
-            "tdiv" => Ok(Token::Instruction(Instruction::Tdiv)),
-
-            "sll" => Ok(Token::Instruction(Instruction::Sll)),
-            "srl" => Ok(Token::Instruction(Instruction::Srl)),
-            "sra" => Ok(Token::Instruction(Instruction::Sra)),
-
-            "mtl" => Ok(Token::Instruction(Instruction::Mtl)),
-            "mfl" => Ok(Token::Instruction(Instruction::Mfl)),
-            "mth" => Ok(Token::Instruction(Instruction::Mth)),
-            "mfh" => Ok(Token::Instruction(Instruction::Mfh)),
-            "mtac" => Ok(Token::Instruction(Instruction::Mtac)),
-            "mfac" => Ok(Token::Instruction(Instruction::Mfac)),
-
-            "addi" => Ok(Token::Instruction(Instruction::Addi)),
-            "subi" => Ok(Token::Instruction(Instruction::Subi)),
-            "andi" => Ok(Token::Instruction(Instruction::Andi)),
-            "ori" => Ok(Token::Instruction(Instruction::Ori)),
-            "xori" => Ok(Token::Instruction(Instruction::Xori)),
-            "nandi" => Ok(Token::Instruction(Instruction::Nandi)),
-            "nori" => Ok(Token::Instruction(Instruction::Nori)),
-            "xnori" => Ok(Token::Instruction(Instruction::Xnori)),
-            "lli" => Ok(Token::Instruction(Instruction::Lli)),
-            "lui" => Ok(Token::Instruction(Instruction::Lui)),
-            "lsi" => Ok(Token::Instruction(Instruction::Lsi)),
-
-            "lwr" => Ok(Token::Instruction(Instruction::Lwr)),
-            "swr" => Ok(Token::Instruction(Instruction::Swr)),
-            "push" => Ok(Token::Instruction(Instruction::Push)),
-            "pop" => Ok(Token::Instruction(Instruction::Pop)),
-
-            "jr" => Ok(Token::Instruction(Instruction::Jr)),
-            "jrl" => Ok(Token::Instruction(Instruction::Jrl)),
-            "ja" => Ok(Token::Instruction(Instruction::Ja)),
-            "jal" => Ok(Token::Instruction(Instruction::Jal)),
-            "bgtz" => Ok(Token::Instruction(Instruction::Bgtz)),
-            "bltz" => Ok(Token::Instruction(Instruction::Bltz)),
-            "beqz" => Ok(Token::Instruction(Instruction::Beqz)),
-            "bnez" => Ok(Token::Instruction(Instruction::Bnez)),
-            "bgtzr" => Ok(Token::Instruction(Instruction::Bgtzr)),
-            "bltzr" => Ok(Token::Instruction(Instruction::Bltzr)),
-            "beqzr" => Ok(Token::Instruction(Instruction::Beqzr)),
-            "bnezr" => Ok(Token::Instruction(Instruction::Bnezr)),
+        if let Some(instruction) = Instruction::from_mnemonic(token_string) {
+            return Ok(Token::Instruction(instruction));
+        }
+        if let Some(psinstr) = PseudoInstruction::from_mnemonic(token_string) {
+            return Ok(Token::PseudoInstruction(psinstr));
+        }
 
+        match token_string.as_str() {
             // Directives
             ".include" => Ok(Token::Directive(Directive::Include)),
             ".data" => Ok(Token::Directive(Directive::Data)),
@@ -88,16 +39,10 @@ impl Token {
             ".ascii" => Ok(Token::Directive(Directive::Ascii)),
             ".byte" => Ok(Token::Directive(Directive::Byte)),
             ".inst" => Ok(Token::Directive(Directive::Inst)),
-
-            // Pseudo instructions
-            "jump" => Ok(Token::PseudoInstruction(PseudoInstruction::Jump)),
-            "lw" => Ok(Token::PseudoInstruction(PseudoInstruction::Lw)),
-            "sw" => Ok(Token::PseudoInstruction(PseudoInstruction::Sw)),
-            "mul" => Ok(Token::PseudoInstruction(PseudoInstruction::Mul)),
-            "div" => Ok(Token::PseudoInstruction(PseudoInstruction::Div)),
-            "swap" => Ok(Token::PseudoInstruction(PseudoInstruction::Swap)),
-            "call" => Ok(Token::PseudoInstruction(PseudoInstruction::Call)),
-            "ret" => Ok(Token::PseudoInstruction(PseudoInstruction::Ret)),
+            ".rodata" => Ok(Token::Directive(Directive::Rodata)),
+            ".bss" => Ok(Token::Directive(Directive::Bss)),
+            ".section" => Ok(Token::Directive(Directive::Section)),
+            ".raw" => Ok(Token::Directive(Directive::Raw)),
             
             "," => Ok(Token::Comma),
             "[" => Ok(Token::LeftSquareBracket),